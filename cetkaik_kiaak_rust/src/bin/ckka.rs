@@ -0,0 +1,396 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::non_ascii_literal)]
+
+use cetkaik_kiaak::body::{Body, Season};
+use cetkaik_kiaak::header::{self, Header};
+use cetkaik_kiaak::rule::Config;
+use cetkaik_kiaak::score::{self, GameEnd};
+use cetkaik_kiaak::serialize::{self, NumeralStyle};
+use cetkaik_kiaak::{
+    body_elem_positions, capture, consistency, declaration, parse_ckka, parse_ckka_bytes, replay,
+    season, turn,
+};
+use std::io::{self, Read, Write};
+use std::process;
+
+const USAGE: &str = "\
+Usage:
+    ckka check [FILE...]
+    ckka fmt [FILE...]
+    ckka convert --to <0|1|2|3> [FILE]
+    ckka info [FILE...]
+
+Commands:
+    check      Parse the records and report what is wrong with them
+    fmt        Rewrite the records in notation 0, with the moves in two columns
+    convert    Write the record in another notation to standard output
+    info       Print the header and the final scores
+
+A record is read from standard input if no FILE is given or FILE is `-`.
+`fmt` rewrites each FILE in place, and writes to standard output a record read from standard input.
+
+Exit status: 0 on success, 1 if a record has an error, 2 if the command line or a file cannot be used.";
+
+/// An error that stops the command as a whole, as opposed to an error found in a record
+enum Failure {
+    Usage(String),
+    Io(String),
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = match run(&args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(Failure::Usage(message)) => {
+            eprintln!("ckka: {message}\n\n{USAGE}");
+            2
+        }
+        Err(Failure::Io(message)) => {
+            eprintln!("ckka: {message}");
+            2
+        }
+    };
+    process::exit(code);
+}
+
+/// Returns whether every record was free of errors.
+fn run(args: &[String]) -> Result<bool, Failure> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Err(Failure::Usage(String::from("no command is given"))),
+    };
+    match command {
+        "-h" | "--help" | "help" => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        "check" => for_each_input(rest, check),
+        "fmt" => for_each_input(rest, fmt),
+        "info" => {
+            let inputs = inputs(rest)?;
+            let many = inputs.len() > 1;
+            let mut ok = true;
+            for (i, name) in inputs.iter().enumerate() {
+                if many {
+                    println!("{}==> {} <==", if i == 0 { "" } else { "\n" }, name);
+                }
+                ok &= info(name)?;
+            }
+            Ok(ok)
+        }
+        "convert" => convert(rest),
+        _ => Err(Failure::Usage(format!("unknown command `{command}`"))),
+    }
+}
+
+/// The files named in the arguments, where `-` stands for standard input.
+fn inputs(args: &[String]) -> Result<Vec<String>, Failure> {
+    if let Some(option) = args.iter().find(|arg| arg.starts_with('-') && *arg != "-") {
+        return Err(Failure::Usage(format!("unknown option `{option}`")));
+    }
+    if args.is_empty() {
+        Ok(vec![String::from("-")])
+    } else {
+        Ok(args.to_vec())
+    }
+}
+
+fn for_each_input(
+    args: &[String],
+    command: fn(&str) -> Result<bool, Failure>,
+) -> Result<bool, Failure> {
+    let mut ok = true;
+    for name in inputs(args)? {
+        ok &= command(&name)?;
+    }
+    Ok(ok)
+}
+
+fn display_name(name: &str) -> &str {
+    if name == "-" {
+        "<stdin>"
+    } else {
+        name
+    }
+}
+
+fn read_input(name: &str) -> Result<Vec<u8>, Failure> {
+    if name == "-" {
+        let mut bytes = vec![];
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| Failure::Io(format!("cannot read standard input: {e}")))?;
+        Ok(bytes)
+    } else {
+        std::fs::read(name).map_err(|e| Failure::Io(format!("cannot read `{name}`: {e}")))
+    }
+}
+
+/// Reads and parses the record, reporting a parse error to standard error.
+/// The text is also returned, so that an issue can be reported at the position of its element.
+fn read_record(name: &str) -> Result<Option<(String, Header, Body)>, Failure> {
+    let bytes = read_input(name)?;
+    match parse_ckka_bytes(&bytes) {
+        Ok((header, body)) => {
+            let text = String::from_utf8(bytes).unwrap_or_default();
+            Ok(Some((text, header, body)))
+        }
+        Err(e) => {
+            eprintln!("{}: {}", display_name(name), e);
+            Ok(None)
+        }
+    }
+}
+
+fn write_output(text: &str) -> Result<(), Failure> {
+    io::stdout()
+        .write_all(text.as_bytes())
+        .map_err(|e| Failure::Io(format!("cannot write to standard output: {e}")))
+}
+
+/// Where the element at `index` begins, or which element it is if the position is not known.
+fn locate(positions: Option<&Vec<cetkaik_kiaak::error::Position>>, index: usize) -> String {
+    match positions.and_then(|positions| positions.get(index)) {
+        Some(position) => position.to_string(),
+        None => format!("element {}", index + 1),
+    }
+}
+
+/// The issues found in a record that parses, each with the index of the element in `Body.0`.
+fn issues(header: &Header, body: &Body) -> (Vec<String>, Vec<(usize, String)>) {
+    let mut in_header = vec![];
+    let mut issues = vec![];
+
+    let config = Config::from_header(header).unwrap_or_else(|e| {
+        in_header.push(e.to_string());
+        Config::standard()
+    });
+    let first_season = header.season().unwrap_or_else(|e| {
+        in_header.push(e.to_string());
+        None
+    });
+
+    /* the checks that replay the body are given the part before a move that cannot be replayed, which is reported once here */
+    let replayable = match replay::replay_with(body, config).find(Result::is_err) {
+        Some(Err(e)) => {
+            issues.push((e.index, format!("cannot replay the move: {}", e.kind)));
+            Body(body.0[..e.index].to_vec())
+        }
+        _ => body.clone(),
+    };
+
+    issues.extend(
+        consistency::check_consistency(body)
+            .into_iter()
+            .map(|e| (e.index, format!("inconsistent move: {}", e.violation))),
+    );
+    if let Ok(errors) = declaration::check_declarations(&replayable) {
+        issues.extend(
+            errors
+                .into_iter()
+                .map(|e| (e.index, format!("declaration: {}", e.issue))),
+        );
+    }
+    if let Ok(errors) = capture::check_capture_comments(&replayable) {
+        issues.extend(
+            errors
+                .into_iter()
+                .map(|e| (e.index, format!("capture comment: {}", e.issue))),
+        );
+    }
+    if let Ok(seasons) = season::track_seasons(&replayable, first_season.unwrap_or(Season::Spring))
+    {
+        issues.extend(
+            seasons
+                .warnings
+                .into_iter()
+                .map(|e| (e.index, format!("season: {}", e.warning))),
+        );
+    }
+    if let Ok(turns) = turn::turns(header, &replayable) {
+        issues.extend(
+            turns
+                .errors
+                .into_iter()
+                .map(|e| (e.index, format!("turn: {}", e.issue))),
+        );
+    }
+    if let Some(ledger) = score::ledger(header, body) {
+        issues.extend(
+            ledger
+                .errors
+                .into_iter()
+                .map(|e| (e.index, format!("score: {}", e.issue))),
+        );
+    }
+
+    issues.sort_by_key(|(index, _)| *index);
+    (in_header, issues)
+}
+
+fn check(name: &str) -> Result<bool, Failure> {
+    let Some((text, header, body)) = read_record(name)? else {
+        return Ok(false);
+    };
+    let (in_header, issues) = issues(&header, &body);
+    let positions = body_elem_positions(&text);
+    for message in &in_header {
+        eprintln!("{}: header: {}", display_name(name), message);
+    }
+    for (index, message) in &issues {
+        eprintln!(
+            "{}: {}: {}",
+            display_name(name),
+            locate(positions.as_ref(), *index),
+            message
+        );
+    }
+    Ok(in_header.is_empty() && issues.is_empty())
+}
+
+fn fmt(name: &str) -> Result<bool, Failure> {
+    let Some((text, header, body)) = read_record(name)? else {
+        return Ok(false);
+    };
+    let body_text = match serialize::serialize_body_in_columns(&body, NumeralStyle::Pekzep) {
+        Ok(body_text) => body_text,
+        Err(e) => {
+            eprintln!(
+                "{}: {}: this element cannot be written in notation 0",
+                display_name(name),
+                locate(body_elem_positions(&text).as_ref(), e.index)
+            );
+            return Ok(false);
+        }
+    };
+    let formatted = format!(
+        "{}{}",
+        serialize::serialize_header(&header, NumeralStyle::Pekzep),
+        body_text
+    );
+
+    /* a body beginning with a parachute would be read as a part of the header */
+    if parse_ckka(&formatted) != Ok((header, body)) {
+        eprintln!(
+            "{}: cannot be formatted without changing what it says",
+            display_name(name)
+        );
+        return Ok(false);
+    }
+
+    if name == "-" {
+        write_output(&formatted)?;
+    } else if formatted != text {
+        std::fs::write(name, formatted)
+            .map_err(|e| Failure::Io(format!("cannot write `{name}`: {e}")))?;
+    }
+    Ok(true)
+}
+
+fn convert(args: &[String]) -> Result<bool, Failure> {
+    let mut notation = None;
+    let mut files = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--to" {
+            notation = Some(
+                args.next()
+                    .ok_or_else(|| Failure::Usage(String::from("`--to` needs a notation")))?
+                    .clone(),
+            );
+        } else if let Some(value) = arg.strip_prefix("--to=") {
+            notation = Some(value.to_owned());
+        } else {
+            files.push(arg.clone());
+        }
+    }
+    let notation =
+        notation.ok_or_else(|| Failure::Usage(String::from("`convert` needs `--to <0|1|2|3>`")))?;
+    let name = match inputs(&files)?.as_slice() {
+        [name] => name.clone(),
+        _ => {
+            return Err(Failure::Usage(String::from(
+                "`convert` reads a single record",
+            )))
+        }
+    };
+
+    let Some((text, header, body)) = read_record(&name)? else {
+        return Ok(false);
+    };
+    let style = NumeralStyle::Pekzep;
+    let (digit, written) = match notation.as_str() {
+        "0" | "⓪" => ('0', serialize::serialize_body(&body, style)),
+        "1" | "①" => ('1', serialize::serialize_body_in_notation1(&body, style)),
+        "2" | "②" => ('2', serialize::serialize_body_in_notation2(&body)),
+        "3" | "③" => {
+            let config = Config::from_header(&header).unwrap_or_else(|e| {
+                eprintln!("{}: header: {}", display_name(&name), e);
+                Config::standard()
+            });
+            (
+                '3',
+                serialize::serialize_body_in_notation3_with(&body, config),
+            )
+        }
+        _ => return Err(Failure::Usage(format!("unknown notation `{notation}`"))),
+    };
+    let body_text = match written {
+        Ok(body_text) => body_text,
+        Err(e) => {
+            eprintln!(
+                "{}: {}: this element cannot be written in notation {}",
+                display_name(&name),
+                locate(body_elem_positions(&text).as_ref(), e.index),
+                digit
+            );
+            return Ok(false);
+        }
+    };
+    write_output(&format!(
+        "{}{}",
+        serialize::serialize_header(&header, style),
+        body_text
+    ))?;
+    Ok(true)
+}
+
+fn info(name: &str) -> Result<bool, Failure> {
+    let Some((_, header, body)) = read_record(name)? else {
+        return Ok(false);
+    };
+    let mut lines: Vec<String> = header
+        .info
+        .iter()
+        .map(|elem| match elem {
+            header::Elem::Value(value) => value.clone(),
+            header::Elem::KeyedValue(key, value) => format!("{key}: {value}"),
+        })
+        .collect();
+
+    match score::ledger(&header, &body) {
+        None => lines.push(String::from("players: not given")),
+        Some(ledger) => {
+            let points = |points: [i64; 2]| {
+                format!(
+                    "[{}] {}, [{}] {}",
+                    ledger.player_names[0], points[0], ledger.player_names[1], points[1]
+                )
+            };
+            lines.push(format!("initial: {}", points(ledger.initial)));
+            lines.push(format!("final: {}", points(ledger.current())));
+            lines.push(format!("taxots: {}", ledger.standings.len()));
+            lines.push(match ledger.end {
+                None => String::from("end: not yet"),
+                Some((_, GameEnd::Declared)) => String::from("end: 星一周"),
+                Some((_, GameEnd::OutOfPoints(i))) => {
+                    format!("end: [{}] has no points left", ledger.player_names[i])
+                }
+            });
+        }
+    }
+    let out: String = lines.into_iter().map(|line| line + "\n").collect();
+    write_output(&out)?;
+    Ok(true)
+}
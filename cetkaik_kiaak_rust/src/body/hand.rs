@@ -0,0 +1,287 @@
+use cetkaik_core::absolute::NonTam2Piece;
+use cetkaik_core::{Color, Profession};
+use std::collections::HashSet;
+
+/// A hand (役) of the standard rules.
+/// Every hand but `王` has a colour-matched variant (`同色...`) that is worth more.
+/// `王` consists of a single piece, so `同色王` is merely another way to write it.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, PartialOrd, Ord)]
+pub enum Hand {
+    /// 王
+    King,
+
+    /// 獣
+    Beasts,
+
+    /// 同色獣
+    SameColorBeasts,
+
+    /// 地心
+    EarthCenter,
+
+    /// 同色地心
+    SameColorEarthCenter,
+
+    /// 馬弓兵
+    HorseBowPawn,
+
+    /// 同色馬弓兵
+    SameColorHorseBowPawn,
+
+    /// 助友
+    Helpers,
+
+    /// 同色助友
+    SameColorHelpers,
+
+    /// 戦集
+    WarGathering,
+
+    /// 同色戦集
+    SameColorWarGathering,
+
+    /// 行行
+    Marching,
+
+    /// 同色行行
+    SameColorMarching,
+
+    /// 筆兵無傾
+    UprightScribeAndPawns,
+
+    /// 同色筆兵無傾
+    SameColorUprightScribeAndPawns,
+
+    /// 闇戦之集
+    DarkWarGathering,
+
+    /// 同色闇戦之集
+    SameColorDarkWarGathering,
+
+    /// 無抗行処
+    UnresistedMarch,
+
+    /// 同色無抗行処
+    SameColorUnresistedMarch,
+}
+
+/// Every hand, in the order of `Hand`.
+pub const ALL_HANDS: [Hand; 19] = [
+    Hand::King,
+    Hand::Beasts,
+    Hand::SameColorBeasts,
+    Hand::EarthCenter,
+    Hand::SameColorEarthCenter,
+    Hand::HorseBowPawn,
+    Hand::SameColorHorseBowPawn,
+    Hand::Helpers,
+    Hand::SameColorHelpers,
+    Hand::WarGathering,
+    Hand::SameColorWarGathering,
+    Hand::Marching,
+    Hand::SameColorMarching,
+    Hand::UprightScribeAndPawns,
+    Hand::SameColorUprightScribeAndPawns,
+    Hand::DarkWarGathering,
+    Hand::SameColorDarkWarGathering,
+    Hand::UnresistedMarch,
+    Hand::SameColorUnresistedMarch,
+];
+
+impl Hand {
+    /// The name of the hand as written in notation ⓪.
+    #[must_use]
+    pub const fn kanji(self) -> &'static str {
+        match self {
+            Hand::King => "王",
+            Hand::Beasts => "獣",
+            Hand::SameColorBeasts => "同色獣",
+            Hand::EarthCenter => "地心",
+            Hand::SameColorEarthCenter => "同色地心",
+            Hand::HorseBowPawn => "馬弓兵",
+            Hand::SameColorHorseBowPawn => "同色馬弓兵",
+            Hand::Helpers => "助友",
+            Hand::SameColorHelpers => "同色助友",
+            Hand::WarGathering => "戦集",
+            Hand::SameColorWarGathering => "同色戦集",
+            Hand::Marching => "行行",
+            Hand::SameColorMarching => "同色行行",
+            Hand::UprightScribeAndPawns => "筆兵無傾",
+            Hand::SameColorUprightScribeAndPawns => "同色筆兵無傾",
+            Hand::DarkWarGathering => "闇戦之集",
+            Hand::SameColorDarkWarGathering => "同色闇戦之集",
+            Hand::UnresistedMarch => "無抗行処",
+            Hand::SameColorUnresistedMarch => "同色無抗行処",
+        }
+    }
+
+    /// Reads the name of a hand. `同色王` is read as `王`.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::body::hand::Hand;
+    /// assert_eq!(Hand::from_kanji("同色獣"), Some(Hand::SameColorBeasts));
+    /// assert_eq!(Hand::from_kanji("同色王"), Some(Hand::King));
+    /// assert_eq!(Hand::from_kanji("同色"), None);
+    /// ```
+    #[must_use]
+    pub fn from_kanji(s: &str) -> Option<Self> {
+        if s == "同色王" {
+            return Some(Hand::King);
+        }
+        ALL_HANDS.iter().copied().find(|hand| hand.kanji() == s)
+    }
+
+    /// The points the hand is worth under the standard rules.
+    #[must_use]
+    pub const fn points(self) -> i64 {
+        match self {
+            Hand::King => 3,
+            Hand::Beasts
+            | Hand::HorseBowPawn
+            | Hand::Helpers
+            | Hand::EarthCenter
+            | Hand::WarGathering
+            | Hand::Marching
+            | Hand::UprightScribeAndPawns => 5,
+            Hand::SameColorBeasts
+            | Hand::SameColorHorseBowPawn
+            | Hand::SameColorHelpers
+            | Hand::SameColorEarthCenter
+            | Hand::SameColorWarGathering
+            | Hand::SameColorMarching
+            | Hand::SameColorUprightScribeAndPawns => 7,
+            Hand::DarkWarGathering | Hand::UnresistedMarch => 10,
+            Hand::SameColorDarkWarGathering | Hand::SameColorUnresistedMarch => 12,
+        }
+    }
+
+    /// Whether the hand requires all of its pieces to share a colour.
+    #[must_use]
+    pub const fn is_same_color(self) -> bool {
+        matches!(
+            self,
+            Hand::SameColorBeasts
+                | Hand::SameColorEarthCenter
+                | Hand::SameColorHorseBowPawn
+                | Hand::SameColorHelpers
+                | Hand::SameColorWarGathering
+                | Hand::SameColorMarching
+                | Hand::SameColorUprightScribeAndPawns
+                | Hand::SameColorDarkWarGathering
+                | Hand::SameColorUnresistedMarch
+        )
+    }
+
+    /// The colour-matched variant of the hand. `王` is its own colour-matched variant.
+    #[must_use]
+    pub const fn same_color(self) -> Self {
+        match self {
+            Hand::Beasts => Hand::SameColorBeasts,
+            Hand::EarthCenter => Hand::SameColorEarthCenter,
+            Hand::HorseBowPawn => Hand::SameColorHorseBowPawn,
+            Hand::Helpers => Hand::SameColorHelpers,
+            Hand::WarGathering => Hand::SameColorWarGathering,
+            Hand::Marching => Hand::SameColorMarching,
+            Hand::UprightScribeAndPawns => Hand::SameColorUprightScribeAndPawns,
+            Hand::DarkWarGathering => Hand::SameColorDarkWarGathering,
+            Hand::UnresistedMarch => Hand::SameColorUnresistedMarch,
+            hand => hand,
+        }
+    }
+
+    /// The hand without the requirement on colour.
+    #[must_use]
+    pub const fn not_same_color(self) -> Self {
+        match self {
+            Hand::SameColorBeasts => Hand::Beasts,
+            Hand::SameColorEarthCenter => Hand::EarthCenter,
+            Hand::SameColorHorseBowPawn => Hand::HorseBowPawn,
+            Hand::SameColorHelpers => Hand::Helpers,
+            Hand::SameColorWarGathering => Hand::WarGathering,
+            Hand::SameColorMarching => Hand::Marching,
+            Hand::SameColorUprightScribeAndPawns => Hand::UprightScribeAndPawns,
+            Hand::SameColorDarkWarGathering => Hand::DarkWarGathering,
+            Hand::SameColorUnresistedMarch => Hand::UnresistedMarch,
+            hand => hand,
+        }
+    }
+
+    /// The pieces that make up the hand, with repetition. A colour-matched hand has the same pieces.
+    #[must_use]
+    pub const fn pieces(self) -> &'static [Profession] {
+        use Profession::{Dau2, Gua2, Io, Kauk2, Kaun1, Kua2, Maun1, Nuak1, Tuk2, Uai1};
+        match self {
+            Hand::King => &[Io],
+            Hand::Beasts | Hand::SameColorBeasts => &[Maun1, Dau2],
+            Hand::EarthCenter | Hand::SameColorEarthCenter => &[Nuak1, Tuk2, Uai1],
+            Hand::HorseBowPawn | Hand::SameColorHorseBowPawn => &[Maun1, Gua2, Kauk2],
+            Hand::Helpers | Hand::SameColorHelpers => &[Kua2, Tuk2],
+            Hand::WarGathering | Hand::SameColorWarGathering => &[Uai1, Maun1, Kaun1, Nuak1],
+            Hand::Marching | Hand::SameColorMarching => &[Kauk2, Kauk2, Kauk2, Kauk2, Kauk2],
+            Hand::UprightScribeAndPawns | Hand::SameColorUprightScribeAndPawns => {
+                &[Kua2, Kauk2, Kauk2, Kauk2]
+            }
+            Hand::DarkWarGathering | Hand::SameColorDarkWarGathering => {
+                &[Uai1, Maun1, Kaun1, Nuak1, Gua2, Dau2]
+            }
+            Hand::UnresistedMarch | Hand::SameColorUnresistedMarch => {
+                &[Kauk2, Kauk2, Kauk2, Kauk2, Kauk2, Kauk2, Kauk2, Kauk2]
+            }
+        }
+    }
+}
+
+/// The hands made by the pieces in a hand (手駒).
+/// When a hand is made in a single colour, only its colour-matched variant is counted.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::hand::{hands_made, Hand};
+/// use cetkaik_core::absolute::NonTam2Piece;
+/// use cetkaik_core::{Color, Profession};
+/// use std::collections::HashSet;
+/// use std::iter::FromIterator;
+/// let pieces = vec![
+///     NonTam2Piece { color: Color::Kok1, prof: Profession::Maun1 },
+///     NonTam2Piece { color: Color::Kok1, prof: Profession::Dau2 },
+///     NonTam2Piece { color: Color::Huok2, prof: Profession::Io },
+/// ];
+/// assert_eq!(
+///     hands_made(&pieces),
+///     HashSet::from_iter(vec![Hand::SameColorBeasts, Hand::King].into_iter())
+/// );
+/// ```
+#[must_use]
+pub fn hands_made(pieces: &[NonTam2Piece]) -> HashSet<Hand> {
+    let all_professions: Vec<_> = pieces.iter().map(|piece| piece.prof).collect();
+    ALL_HANDS
+        .iter()
+        .copied()
+        .filter(|hand| !hand.is_same_color())
+        .filter_map(|hand| {
+            let in_one_color = [Color::Huok2, Color::Kok1].iter().any(|color| {
+                let professions: Vec<_> = pieces
+                    .iter()
+                    .filter(|piece| piece.color == *color)
+                    .map(|piece| piece.prof)
+                    .collect();
+                contains_all(&professions, hand.pieces())
+            });
+            if in_one_color {
+                Some(hand.same_color())
+            } else if contains_all(&all_professions, hand.pieces()) {
+                Some(hand)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn contains_all(professions: &[Profession], needed: &[Profession]) -> bool {
+    needed.iter().all(|prof| {
+        let count = |list: &[Profession]| list.iter().filter(|p| *p == prof).count();
+        count(professions) >= count(needed)
+    })
+}
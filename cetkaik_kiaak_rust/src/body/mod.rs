@@ -1,50 +1,296 @@
+use super::header::PlayerAndPoint;
+use crate::error::{expect, opt_unless_begun, Expected, IResult};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{char, one_of};
-use nom::combinator::map;
-use nom::multi::many1;
-use nom::IResult;
+use nom::combinator::{map, opt};
+use nom::multi::{many0, many1};
 use std::collections::HashSet;
 
+pub mod hand;
 pub mod movement;
+pub mod notation1;
+pub mod notation2;
+pub mod notation3;
 
-#[derive(Eq, PartialEq, Clone, Debug)]
-pub struct Body(pub Vec<Elem>);
+/// The notations in which a body can be written.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Notation {
+    /// 表記法⓪, which every implementation must support
+    Zero,
+
+    /// 表記法①, which leaves out `無撃裁`, `橋` and `水`
+    One,
+
+    /// 表記法②, which is based on the Pekzep pronunciation, such as `TU dau2 XY.`
+    Two,
+
+    /// 表記法③, the machine-readable format of `cerke_noter`, such as `"mauAmaimy1"`
+    Three,
+}
+
+/// The elements of a body, each with the punctuation mark written after it.
+/// The punctuation mark is `None` where an element is followed only by spaces or newlines.
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "crate::json::BodyRepr", into = "crate::json::BodyRepr")
+)]
+pub struct Body(pub Vec<(Elem, Option<Punctuation>)>);
+
+impl Body {
+    /// The elements, without the punctuation marks.
+    #[must_use]
+    pub fn elems(&self) -> impl DoubleEndedIterator<Item = &Elem> + ExactSizeIterator {
+        self.0.iter().map(|(elem, _)| elem)
+    }
+
+    /// The punctuation mark written after the element at `index`, if any.
+    #[must_use]
+    pub fn punctuation_after(&self, index: usize) -> Option<Punctuation> {
+        self.0.get(index).and_then(|(_, punctuation)| *punctuation)
+    }
+}
+
+/// A body whose elements are written without punctuation marks.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::{Body, Elem};
+/// let body = Body::from(vec![Elem::GameEnd]);
+/// assert_eq!(body.0, vec![(Elem::GameEnd, None)]);
+/// ```
+impl From<Vec<Elem>> for Body {
+    fn from(elems: Vec<Elem>) -> Self {
+        Body(elems.into_iter().map(|elem| (elem, None)).collect())
+    }
+}
+
+/// A punctuation mark that separates body elements, such as `、`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+pub enum Punctuation {
+    /// `,` (U+002C)
+    Comma,
+
+    /// `.` (U+002E)
+    FullStop,
+
+    /// `、` (U+3001)
+    IdeographicComma,
+
+    /// `。` (U+3002)
+    IdeographicFullStop,
+
+    /// `，` (U+FF0C)
+    FullwidthComma,
+
+    /// `．` (U+FF0E)
+    FullwidthFullStop,
+
+    /// `｡` (U+FF61)
+    HalfwidthIdeographicFullStop,
+
+    /// `､` (U+FF64)
+    HalfwidthIdeographicComma,
+}
+
+impl Punctuation {
+    #[must_use]
+    pub const fn to_char(self) -> char {
+        match self {
+            Punctuation::Comma => ',',
+            Punctuation::FullStop => '.',
+            Punctuation::IdeographicComma => '、',
+            Punctuation::IdeographicFullStop => '。',
+            Punctuation::FullwidthComma => '，',
+            Punctuation::FullwidthFullStop => '．',
+            Punctuation::HalfwidthIdeographicFullStop => '｡',
+            Punctuation::HalfwidthIdeographicComma => '､',
+        }
+    }
+
+    #[must_use]
+    pub const fn from_char(c: char) -> Option<Self> {
+        match c {
+            ',' => Some(Punctuation::Comma),
+            '.' => Some(Punctuation::FullStop),
+            '、' => Some(Punctuation::IdeographicComma),
+            '。' => Some(Punctuation::IdeographicFullStop),
+            '，' => Some(Punctuation::FullwidthComma),
+            '．' => Some(Punctuation::FullwidthFullStop),
+            '｡' => Some(Punctuation::HalfwidthIdeographicFullStop),
+            '､' => Some(Punctuation::HalfwidthIdeographicComma),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "crate::json::ElemRepr", into = "crate::json::ElemRepr")
+)]
 pub enum Elem {
-    Move(movement::Move),
-    CaptureComment(cetkaik_core::Profession),
+    Move(movement::Move, Option<String>),
+
+    /// The piece captured by the preceding move, such as `手兵` or `手黒馬`
+    CaptureComment(
+        movement::PossiblyUnknown<cetkaik_core::Color>,
+        cetkaik_core::Profession,
+    ),
+
     TaXotTyMok(HandCreation, Action),
     SeasonEnd(Season),
     GameEnd,
+
+    /// The points of both players at that moment, such as `[JV]十二 [SY] 二十八`
+    Points(PlayerAndPoint, PlayerAndPoint),
 }
 
 use nom::combinator::eof;
 
 pub fn parse_body_elem(s: &str) -> IResult<&str, Elem> {
+    map(parse_body_elem_with_punctuation, |(elem, _)| elem)(s)
+}
+
+/// Parses a body element together with what separates it from the next one,
+/// keeping the punctuation mark if there is one.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::{parse_body_elem_with_punctuation, Punctuation};
+/// let (rest, (_, punctuation)) = parse_body_elem_with_punctuation("XU兵XY無撃裁、XAI兵XY無撃裁").unwrap();
+/// assert_eq!(rest, "XAI兵XY無撃裁");
+/// assert_eq!(punctuation, Some(Punctuation::IdeographicComma));
+/// let (rest, (_, punctuation)) = parse_body_elem_with_punctuation("XU兵XY無撃裁\n手兵").unwrap();
+/// assert_eq!(rest, "手兵");
+/// assert_eq!(punctuation, None);
+/// assert!(parse_body_elem_with_punctuation("XU兵XY無撃裁;").is_err());
+/// ```
+pub fn parse_body_elem_with_punctuation(s: &str) -> IResult<&str, (Elem, Option<Punctuation>)> {
+    parse_body_elem_using(s, movement::parse)
+}
+
+pub(crate) fn parse_body_elem_using(
+    s: &str,
+    move_parser: fn(&str) -> IResult<&str, movement::Move>,
+) -> IResult<&str, (Elem, Option<Punctuation>)> {
     let (r, body_elem) = alt((
-        map(movement::parse, Elem::Move),
-        map(parse_game_end, |_| Elem::GameEnd),
+        map(
+            |s| parse_move_with_comment_using(s, move_parser),
+            |(m, comment)| Elem::Move(m, comment),
+        ),
+        map(parse_game_end, |()| Elem::GameEnd),
         map(parse_season_end, Elem::SeasonEnd),
         map(parse_ty_mok_ta_xot, |(a, b)| Elem::TaXotTyMok(a, b)),
-        map(parse_capture_comment, Elem::CaptureComment),
+        map(parse_points, |(a, b)| Elem::Points(a, b)),
+        map(parse_capture_comment, |(color, prof)| {
+            Elem::CaptureComment(color, prof)
+        }),
     ))(s)?;
-    let (no_used, _) = alt((
-        map(many1(one_of("\t\r\n \u{00a0}\u{3000}")), |_| ()),
-        map(eof, |_| ()),
-    ))(r)?;
+    let (no_used, punctuation) = parse_separator(r)?;
 
-    Ok((no_used, body_elem))
+    Ok((no_used, (body_elem, punctuation)))
+}
+
+/// Spaces and newlines, a punctuation mark, or both, possibly with spaces and newlines on either side.
+/// The end of the input also separates elements.
+pub(crate) fn parse_separator(s: &str) -> IResult<&str, Option<Punctuation>> {
+    let (rest, before) = many0(one_of("\t\r\n \u{00a0}\u{3000}"))(s)?;
+    let (rest, punctuation) = opt(parse_punctuation)(rest)?;
+    let (rest, _) = many0(one_of("\t\r\n \u{00a0}\u{3000}"))(rest)?;
+    if before.is_empty() && punctuation.is_none() {
+        expect(Expected::Separator, eof)(rest)?;
+    }
+    Ok((rest, punctuation))
+}
+
+fn parse_punctuation(s: &str) -> IResult<&str, Punctuation> {
+    map(one_of(".,、。，．｡､"), |c| {
+        Punctuation::from_char(c).expect("every character listed is a punctuation mark")
+    })(s)
+}
+
+/// ```
+/// use cetkaik_kiaak::body::parse_move_with_comment;
+/// use cetkaik_kiaak::body::movement::Move;
+/// use cetkaik_core::Profession;
+/// use cetkaik_core::absolute::*;
+/// assert_eq!(
+///     parse_move_with_comment("LE弓LILU橋二{定弓}"),
+///     Ok((
+///         "",
+///         (
+///             Move::StepAndBridgeStick {
+///                 src: Coord(Row::E, Column::L),
+///                 prof: Some(Profession::Gua2),
+///                 step: Coord(Row::I, Column::L),
+///                 dest: Coord(Row::U, Column::L),
+///                 bridge_stick_size: Some(2),
+///                 bridge_stick_successful: true,
+///             },
+///             Some(String::from("定弓"))
+///         )
+///     ))
+/// );
+/// assert_eq!(
+///     parse_move_with_comment("XU兵XY無撃裁#{}}#"),
+///     Ok((
+///         "",
+///         (
+///             Move::NoStepAndNoStick {
+///                 src: Coord(Row::U, Column::X),
+///                 prof: Some(Profession::Kauk2),
+///                 dest: Coord(Row::Y, Column::X),
+///             },
+///             Some(String::from("}"))
+///         )
+///     ))
+/// );
+/// ```
+pub fn parse_move_with_comment(s: &str) -> IResult<&str, (movement::Move, Option<String>)> {
+    parse_move_with_comment_using(s, movement::parse)
+}
+
+fn parse_move_with_comment_using(
+    s: &str,
+    move_parser: fn(&str) -> IResult<&str, movement::Move>,
+) -> IResult<&str, (movement::Move, Option<String>)> {
+    let (rest, m) = move_parser(s)?;
+    let (rest, comment) = opt_unless_begun(parse_move_comment)(rest)?;
+    Ok((rest, (m, comment)))
+}
+
+/// Parses the comment `{...}` (or `#{...}#` and so on) that may follow a movement element.
+/// Unlike the header, this does not consume the spaces that follow, since they act as the separator.
+pub fn parse_move_comment(s: &str) -> IResult<&str, String> {
+    let (rest, comment) = super::parse_braced_string_without_trailing_spaces(s, '{', '}')?;
+    Ok((rest, comment.to_owned()))
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HandCreation {
-    pub player_name: String,
-    pub hands: HashSet<String>,
+    /// The player who made the hands, unless the notation leaves the player out
+    pub player_name: movement::PossiblyUnknown<String>,
+
+    /// The hands made, unless the notation leaves them out
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::hands"))]
+    pub hands: movement::PossiblyUnknown<HashSet<hand::Hand>>,
+
+    /// The points of the hands as the record writes them, such as `而手三` in notation ① or `hop1 om2` in notation ②.
+    /// These are not the points that a taxot pays out, which follow `終季`.
+    pub points: movement::PossiblyUnknown<i64>,
 }
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Season {
     Spring,
     Summer,
@@ -54,41 +300,110 @@ pub enum Season {
     Down,
 }
 
+/// Parses a line of points such as `[JV]十二 [SY] 二十八`.
+/// Unlike the header, both points must be written.
+///
+/// ```
+/// use cetkaik_kiaak::body::parse_points;
+/// use cetkaik_kiaak::header::PlayerAndPoint;
+/// assert_eq!(
+///     parse_points("[JV]十二 [SY] 二十八"),
+///     Ok((
+///         "",
+///         (
+///             PlayerAndPoint { player_name: String::from("JV"), point: 12 },
+///             PlayerAndPoint { player_name: String::from("SY"), point: 28 }
+///         )
+///     ))
+/// );
+/// assert!(parse_points("[JV]十二 [SY]").is_err());
+/// ```
+pub fn parse_points(s: &str) -> IResult<&str, (PlayerAndPoint, PlayerAndPoint)> {
+    let (rest, first) = parse_player_and_point(s)?;
+    let (rest, _) = expect(Expected::Separator, many1(one_of("\t \u{00a0}\u{3000}")))(rest)?;
+    let (rest, second) = expect(Expected::Player, parse_player_and_point)(rest)?;
+    Ok((rest, (first, second)))
+}
+
+pub(crate) fn parse_player_and_point(s: &str) -> IResult<&str, PlayerAndPoint> {
+    let (rest, player_name) = super::parse_braced_string_without_trailing_spaces(s, '[', ']')?;
+    let (rest, _) = many0(one_of("\t \u{00a0}\u{3000}"))(rest)?;
+    let (rest, point) = super::parse_numeral(rest)?;
+    Ok((
+        rest,
+        PlayerAndPoint {
+            player_name: player_name.to_owned(),
+            point,
+        },
+    ))
+}
+
 pub fn parse_game_end(s: &str) -> IResult<&str, ()> {
     let (rest, _) = tag("星一周")(s)?;
     Ok((rest, ()))
 }
 
-pub fn parse_season_end(s: &str) -> IResult<&str, Season> {
-    let (rest, season) = alt((
+/// Parses the name of a season, such as `夏` or `上季`.
+pub fn parse_season(s: &str) -> IResult<&str, Season> {
+    alt((
         map(tag("春"), |_| Season::Spring),
         map(tag("夏"), |_| Season::Summer),
         map(tag("秋"), |_| Season::Fall),
         map(tag("冬"), |_| Season::Winter),
         map(tag("上季"), |_| Season::Up),
         map(tag("下季"), |_| Season::Down),
-    ))(s)?;
-    let (rest, _) = tag("終")(rest)?;
+    ))(s)
+}
+
+pub fn parse_season_end(s: &str) -> IResult<&str, Season> {
+    let (rest, season) = parse_season(s)?;
+    let (rest, _) = expect(Expected::Literal("終"), tag("終"))(rest)?;
     Ok((rest, season))
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "points", rename_all = "snake_case")
+)]
 pub enum Action {
-    TaXot(i64),
+    /// The points moved by the taxot, if the record states them
+    TaXot(movement::PossiblyUnknown<i64>),
     TyMok,
 }
 
-pub fn parse_capture_comment(s: &str) -> IResult<&str, cetkaik_core::Profession> {
+/// Parses `手`, followed by the colour if it is written, and the profession.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::parse_capture_comment;
+/// use cetkaik_core::{Color, Profession};
+/// assert_eq!(parse_capture_comment("手兵"), Ok(("", (None, Profession::Kauk2))));
+/// assert_eq!(
+///     parse_capture_comment("手黒馬"),
+///     Ok(("", (Some(Color::Huok2), Profession::Maun1)))
+/// );
+/// ```
+pub fn parse_capture_comment(
+    s: &str,
+) -> IResult<
+    &str,
+    (
+        movement::PossiblyUnknown<cetkaik_core::Color>,
+        cetkaik_core::Profession,
+    ),
+> {
     let (rest, _) = char('手')(s)?;
+    let (rest, color) = opt(movement::parse_color)(rest)?;
     let (rest, prof) = movement::parse_profession(rest)?;
 
-    Ok((rest, prof))
+    Ok((rest, (color, prof)))
 }
 
 /// ```
 /// use cetkaik_kiaak::body::{parse_ty_mok_ta_xot, HandCreation, Action};
-/// use cetkaik_core::Profession;
-/// use cetkaik_core::absolute::*;
+/// use cetkaik_kiaak::body::hand::Hand;
 /// use std::collections::HashSet;
 /// use std::iter::FromIterator;
 /// assert_eq!(
@@ -96,8 +411,9 @@ pub fn parse_capture_comment(s: &str) -> IResult<&str, cetkaik_core::Profession>
 ///     Ok((
 ///         "",
 ///         (HandCreation {
-///             player_name: String::from("SY"),
-///             hands: HashSet::from_iter(vec![String::from("獣"), String::from("同色馬弓兵")].into_iter())
+///             player_name: Some(String::from("SY")),
+///             hands: Some(HashSet::from_iter(vec![Hand::Beasts, Hand::SameColorHorseBowPawn].into_iter())),
+///             points: None
 ///         }, Action::TyMok)
 ///     ))
 /// );
@@ -106,9 +422,10 @@ pub fn parse_capture_comment(s: &str) -> IResult<&str, cetkaik_core::Profession>
 ///     Ok((
 ///         "",
 ///         (HandCreation {
-///             player_name: String::from("SY"),
-///             hands: HashSet::from_iter(vec![String::from("獣"), String::from("同色馬弓兵")].into_iter())
-///         }, Action::TaXot(10))
+///             player_name: Some(String::from("SY")),
+///             hands: Some(HashSet::from_iter(vec![Hand::Beasts, Hand::SameColorHorseBowPawn].into_iter())),
+///             points: None
+///         }, Action::TaXot(Some(10)))
 ///     ))
 /// );
 /// assert_eq!(
@@ -116,29 +433,38 @@ pub fn parse_capture_comment(s: &str) -> IResult<&str, cetkaik_core::Profession>
 ///     Ok((
 ///         "",
 ///         (HandCreation {
-///             player_name: String::from("SY"),
-///             hands: HashSet::from_iter(vec![String::from("獣"), String::from("同色馬弓兵")].into_iter())
-///         }, Action::TaXot(20))
+///             player_name: Some(String::from("SY")),
+///             hands: Some(HashSet::from_iter(vec![Hand::Beasts, Hand::SameColorHorseBowPawn].into_iter())),
+///             points: None
+///         }, Action::TaXot(Some(20)))
 ///     ))
 /// );
 /// ```
 pub fn parse_ty_mok_ta_xot(s: &str) -> IResult<&str, (HandCreation, Action)> {
     let (rest, hand_creation) = parse_hand_creation(s)?;
-    let (rest, action) = alt((map(tag("再行"), |_| Action::TyMok), |s| {
-        let (r, _) = tag("終季")(s)?;
-        let (r, _) = many1(one_of("\t\r\n \u{00a0}\u{3000}"))(r)?;
-        let (r, _) = tag("手")(r)?;
-        let (r, num) = super::parse_numeral(r)?;
-        Ok((r, Action::TaXot(num)))
-    }))(rest)?;
+    let (rest, action) = expect(
+        Expected::Action,
+        alt((map(tag("再行"), |_| Action::TyMok), |s| {
+            let (r, _) = tag("終季")(s)?;
+            let (r, _) = expect(
+                Expected::Separator,
+                many1(one_of("\t\r\n \u{00a0}\u{3000}")),
+            )(r)?;
+            let (r, _) = expect(Expected::Literal("手"), tag("手"))(r)?;
+            let (r, num) = super::parse_numeral(r)?;
+            Ok((r, Action::TaXot(Some(num))))
+        })),
+    )(rest)?;
 
     Ok((rest, (hand_creation, action)))
 }
 
+/// Parses `[player]為` followed by the hands. A name that is not a standard hand is rejected,
+/// and so is writing both `(王)` and `(同色王)`, since they are the same hand.
+///
 /// ```
 /// use cetkaik_kiaak::body::{parse_hand_creation, HandCreation};
-/// use cetkaik_core::Profession;
-/// use cetkaik_core::absolute::*;
+/// use cetkaik_kiaak::body::hand::Hand;
 /// use std::collections::HashSet;
 /// use std::iter::FromIterator;
 /// assert_eq!(
@@ -146,25 +472,60 @@ pub fn parse_ty_mok_ta_xot(s: &str) -> IResult<&str, (HandCreation, Action)> {
 ///     Ok((
 ///         "",
 ///         HandCreation {
-///             player_name: String::from("SY"),
-///             hands: HashSet::from_iter(vec![String::from("獣"), String::from("同色馬弓兵")].into_iter())
+///             player_name: Some(String::from("SY")),
+///             hands: Some(HashSet::from_iter(vec![Hand::Beasts, Hand::SameColorHorseBowPawn].into_iter())),
+///             points: None
 ///         }
 ///     ))
 /// );
+/// assert!(parse_hand_creation("[SY]為(獣)(同色)").is_err());
+/// assert!(parse_hand_creation("[SY]為(王)(同色王)").is_err());
 /// ```
 pub fn parse_hand_creation(s: &str) -> IResult<&str, HandCreation> {
     let (rest, player_name) = super::parse_braced_string(s, '[', ']')?;
-    let (rest, _) = char('為')(rest)?;
-    let (rest, hands) = many1(|s| super::parse_braced_string(s, '(', ')'))(rest)?;
+    let (mut rest, _) = expect(Expected::Literal("為"), char('為'))(rest)?;
+    let mut names = vec![];
+    let mut hands = HashSet::new();
+    loop {
+        /* a name that is read but is not a hand is reported as it is, rather than ending the list */
+        let (after, name) = match super::parse_braced_string(rest, '(', ')') {
+            Ok(read) => read,
+            Err(nom::Err::Error(e)) if e.remaining() == rest.len() => {
+                if !names.is_empty() {
+                    break;
+                }
+                return Err(nom::Err::Error(e.expecting(rest, Expected::Literal("("))));
+            }
+            Err(e) => return Err(e.map(|e| e.expecting(rest, Expected::Hand))),
+        };
+        names.push(name);
+        hands = read_hand_names(&names).ok_or_else(|| {
+            nom::Err::Error(
+                crate::error::Error::new(after, nom::error::ErrorKind::Verify)
+                    .expecting(rest, Expected::Hand),
+            )
+        })?;
+        rest = after;
+    }
 
     Ok((
         rest,
         HandCreation {
-            player_name: player_name.to_owned(),
-            hands: hands
-                .into_iter()
-                .map(std::borrow::ToOwned::to_owned)
-                .collect(),
+            player_name: Some(player_name.to_owned()),
+            hands: Some(hands),
+            points: None,
         },
     ))
 }
+
+/// Reads the names of the hands declared, or returns `None` if a name is not a standard hand
+/// or if both `王` and `同色王` are written, since they are the same hand written in two ways.
+pub(crate) fn read_hand_names(names: &[&str]) -> Option<HashSet<hand::Hand>> {
+    if names.contains(&"王") && names.contains(&"同色王") {
+        return None;
+    }
+    names
+        .iter()
+        .map(|name| hand::Hand::from_kanji(name))
+        .collect()
+}
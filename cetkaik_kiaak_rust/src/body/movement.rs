@@ -1,33 +1,52 @@
+use crate::error::IResult;
+use crate::error::{expect, Error, Expected};
 use cetkaik_core::absolute;
 use nom::branch::alt;
 use nom::character::complete::{char, one_of};
 use nom::combinator::map;
 use nom::combinator::opt;
-use nom::error::{Error, ErrorKind};
+use nom::error::ErrorKind;
 use nom::multi::many_m_n;
 use nom::Err;
-use nom::IResult;
 
-type PossiblyUnknown<T> = Option<T>;
+pub type PossiblyUnknown<T> = Option<T>;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "snake_case")
+)]
 pub enum Move {
     NoStepAndNoStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         dest: absolute::Coord,
     },
 
     NoStepAndWaterStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         dest: absolute::Coord,
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::stick_size"))]
         water_stick_size: PossiblyUnknown<i32>,
         water_stick_successful: bool,
     },
 
     StepAndNoStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         step: absolute::Coord,
         dest: absolute::Coord,
@@ -35,29 +54,45 @@ pub enum Move {
 
     StepAndWaterStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         step: absolute::Coord,
         dest: absolute::Coord,
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::stick_size"))]
         water_stick_size: PossiblyUnknown<i32>,
         water_stick_successful: bool,
     },
 
     StepAndBridgeStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         step: absolute::Coord,
         dest: absolute::Coord,
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::stick_size"))]
         bridge_stick_size: PossiblyUnknown<i32>,
         bridge_stick_successful: bool,
     },
 
     StepAndBridgeStickAndWaterStick {
         src: absolute::Coord,
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::json::possibly_unknown_profession")
+        )]
         prof: PossiblyUnknown<cetkaik_core::Profession>,
         step: absolute::Coord,
         dest: absolute::Coord,
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::stick_size"))]
         bridge_stick_size: PossiblyUnknown<i32>,
         /* The fact that water_stick_size exists assert that bridge_stick was successful */
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::stick_size"))]
         water_stick_size: PossiblyUnknown<i32>,
         water_stick_successful: bool,
     },
@@ -89,7 +124,10 @@ pub enum Move {
     },
 
     Parachute {
-        color: cetkaik_core::Color,
+        /// `None` for `或`, which only notation ① allows
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::possibly_unknown_color"))]
+        color: PossiblyUnknown<cetkaik_core::Color>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::json::profession"))]
         prof: cetkaik_core::Profession,
         dest: absolute::Coord,
     },
@@ -294,7 +332,7 @@ pub enum Move {
 ///     Ok((
 ///         "",
 ///         Move::Parachute {
-///             color: cetkaik_core::Color::Huok2,
+///             color: Some(cetkaik_core::Color::Huok2),
 ///             prof: Profession::Gua2,
 ///             dest: Coord(Row::Y, Column::M),
 ///         }
@@ -305,7 +343,7 @@ pub enum Move {
 ///     Ok((
 ///         "",
 ///         Move::Parachute {
-///             color: cetkaik_core::Color::Kok1,
+///             color: Some(cetkaik_core::Color::Kok1),
 ///             prof: Profession::Kaun1,
 ///             dest: Coord(Row::I, Column::C),
 ///         }
@@ -429,7 +467,7 @@ fn parse_no_step_and_no_stick(s: &str) -> IResult<&str, Move> {
     let (rem, src) = parse_square(s)?;
     let (rem, prof) = parse_profession_or_wildcard(rem)?;
     let (rem, dest) = parse_square(rem)?;
-    let (rem, _) = tag("無撃裁")(rem)?;
+    let (rem, _) = expect(Expected::Stick, tag("無撃裁"))(rem)?;
 
     Ok((rem, Move::NoStepAndNoStick { src, prof, dest }))
 }
@@ -457,7 +495,7 @@ fn parse_step_and_no_stick(s: &str) -> IResult<&str, Move> {
     let (rem, prof) = parse_profession_or_wildcard(rem)?;
     let (rem, step) = parse_square(rem)?;
     let (rem, dest) = parse_square(rem)?;
-    let (rem, _) = tag("無撃裁")(rem)?;
+    let (rem, _) = expect(Expected::Stick, tag("無撃裁"))(rem)?;
 
     Ok((
         rem,
@@ -602,16 +640,29 @@ fn parse_tam_step_during_latter(s: &str) -> IResult<&str, Move> {
     ))
 }
 
-fn parse_parachute(s: &str) -> IResult<&str, Move> {
+/// Parses `黒` or `赤`.
+pub fn parse_color(s: &str) -> IResult<&str, cetkaik_core::Color> {
     let (rem, color) = one_of("黒赤")(s)?;
     let color = match color {
         '黒' => cetkaik_core::Color::Huok2,
         '赤' => cetkaik_core::Color::Kok1,
         _ => unreachable!(),
     };
+    Ok((rem, color))
+}
+
+fn parse_parachute(s: &str) -> IResult<&str, Move> {
+    let (rem, color) = parse_color(s)?;
     let (rem, prof) = parse_profession(rem)?;
     let (rem, dest) = parse_square(rem)?;
-    Ok((rem, Move::Parachute { color, prof, dest }))
+    Ok((
+        rem,
+        Move::Parachute {
+            color: Some(color),
+            prof,
+            dest,
+        },
+    ))
 }
 
 /// Examples:
@@ -625,7 +676,7 @@ fn parse_parachute(s: &str) -> IResult<&str, Move> {
 pub fn parse_tam_sqbracket(s: &str) -> IResult<&str, PossiblyUnknown<absolute::Coord>> {
     let (rem, _) = char('[')(s)?;
     let (rem, opt_coord) = alt((map(parse_square, Some), map(char('或'), |_| None)))(rem)?;
-    let (rem, _) = char(']')(rem)?;
+    let (rem, _) = expect(Expected::ClosingBracket(']'), char(']'))(rem)?;
 
     Ok((rem, opt_coord))
 }
@@ -633,7 +684,7 @@ pub fn parse_tam_sqbracket(s: &str) -> IResult<&str, PossiblyUnknown<absolute::C
 use std::collections::HashMap;
 fn one_of_and_map<'a, U, Error: nom::error::ParseError<&'a str>>(
     list: HashMap<char, U>,
-) -> impl Fn(&'a str) -> IResult<&str, U, Error>
+) -> impl Fn(&'a str) -> nom::IResult<&str, U, Error>
 where
     U: Copy,
 {
@@ -658,7 +709,9 @@ use maplit::hashmap;
 ///
 pub fn parse_profession(s: &str) -> IResult<&str, cetkaik_core::Profession> {
     use cetkaik_core::Profession;
-    one_of_and_map(hashmap![
+    expect(
+        Expected::Profession,
+        one_of_and_map(hashmap![
         '船' => Profession::Nuak1,
         '兵' => Profession::Kauk2,
         '弓' => Profession::Gua2,
@@ -669,7 +722,8 @@ pub fn parse_profession(s: &str) -> IResult<&str, cetkaik_core::Profession> {
         '巫' => Profession::Tuk2,
         '将' => Profession::Uai1,
         '王' => Profession::Io,
-    ])(s)
+        ]),
+    )(s)
 }
 
 /// Examples:
@@ -685,7 +739,9 @@ pub fn parse_profession_or_wildcard(
     s: &str,
 ) -> IResult<&str, PossiblyUnknown<cetkaik_core::Profession>> {
     use cetkaik_core::Profession;
-    one_of_and_map(hashmap! {
+    expect(
+        Expected::ProfessionOrTam,
+        one_of_and_map(hashmap! {
         '船' => Some(Profession::Nuak1),
         '兵' => Some(Profession::Kauk2),
         '弓' => Some(Profession::Gua2),
@@ -697,12 +753,15 @@ pub fn parse_profession_or_wildcard(
         '将' => Some(Profession::Uai1),
         '王' => Some(Profession::Io),
         '片' => None
-    })(s)
+        }),
+    )(s)
 }
 
 pub fn parse_bridge_stick_size(s: &str) -> IResult<&str, PossiblyUnknown<i32>> {
-    let (rem, _) = char('橋')(s)?;
-    one_of_and_map(hashmap! {
+    let (rem, _) = expect(Expected::Stick, char('橋'))(s)?;
+    expect(
+        Expected::BridgeStickSize,
+        one_of_and_map(hashmap! {
         '或' => None,
         '無' => Some(0),
         '一' => Some(1),
@@ -710,11 +769,17 @@ pub fn parse_bridge_stick_size(s: &str) -> IResult<&str, PossiblyUnknown<i32>> {
         '三' => Some(3),
         '四' => Some(4),
         '五' => Some(5),
-    })(rem)
+        }),
+    )(rem)
 }
 
+/// Parses `水` and the value of the water stick. Once `水` is read, a value that cannot be read is reported at `水`.
 pub fn parse_water_stick(s: &str) -> IResult<&str, (PossiblyUnknown<i32>, bool)> {
-    let (rem, _) = char('水')(s)?;
+    expect(Expected::WaterStick, parse_water_stick_after_char)(s)
+}
+
+fn parse_water_stick_after_char(s: &str) -> IResult<&str, (PossiblyUnknown<i32>, bool)> {
+    let (rem, _) = expect(Expected::Stick, char('水'))(s)?;
     let (rem, vec) = many_m_n(1, 3, one_of("或無一二三四五此"))(rem)?;
 
     let result = match vec.as_slice() {
@@ -724,7 +789,7 @@ pub fn parse_water_stick(s: &str) -> IResult<&str, (PossiblyUnknown<i32>, bool)>
         ['三'] => (Some(3), true),
         ['四'] => (Some(4), true),
         ['五'] => (Some(5), true),
-        ['或'] => (None, true), /* unspecified but successful */
+        ['或'] => (None, true),              /* unspecified but successful */
         ['或', '此', '無'] => (None, false), /* unspecified but not successful */
         _ => return Err(Err::Error(Error::new(rem, ErrorKind::Verify))),
         /*(
@@ -737,6 +802,10 @@ pub fn parse_water_stick(s: &str) -> IResult<&str, (PossiblyUnknown<i32>, bool)>
 }
 
 pub fn parse_square(s: &str) -> IResult<&str, absolute::Coord> {
+    expect(Expected::Square, parse_square_unnamed)(s)
+}
+
+fn parse_square_unnamed(s: &str) -> IResult<&str, absolute::Coord> {
     let (rem, column) = one_of("KLNTZXCMP")(s)?;
     let (rem, row) = many_m_n(1, 2, one_of("AEIOUY"))(rem)?;
 
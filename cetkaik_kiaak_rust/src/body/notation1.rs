@@ -0,0 +1,540 @@
+use super::hand::ALL_HANDS;
+use super::movement::{
+    self, parse_profession, parse_profession_or_wildcard, parse_square, Move, PossiblyUnknown,
+};
+use super::{Action, HandCreation};
+use crate::error::IResult;
+use crate::error::{Error, Expected};
+use cetkaik_core::absolute;
+use cetkaik_core::Profession;
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, one_of};
+use nom::combinator::{map, opt};
+use nom::error::ErrorKind;
+use nom::multi::{many0, many1, many_m_n};
+use nom::sequence::preceded;
+use nom::Err;
+
+/// Parses a movement element written in notation ①, in which `無撃裁`, `橋` and `水` are left out.
+/// Anything that is valid in notation ⓪ is also accepted.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation1::parse;
+/// use cetkaik_kiaak::body::movement::Move;
+/// use cetkaik_core::Profession;
+/// use cetkaik_core::absolute::*;
+/// assert_eq!(
+///     parse("XU兵XYXAU"),
+///     Ok((
+///         "",
+///         Move::StepAndNoStick {
+///             src: Coord(Row::U, Column::X),
+///             prof: Some(Profession::Kauk2),
+///             step: Coord(Row::Y, Column::X),
+///             dest: Coord(Row::AU, Column::X)
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ME弓MIMU四"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::I, Column::M),
+///             dest: Coord(Row::U, Column::M),
+///             bridge_stick_size: Some(4),
+///             bridge_stick_successful: true,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ME弓MIMU"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::I, Column::M),
+///             dest: Coord(Row::U, Column::M),
+///             bridge_stick_size: None,
+///             bridge_stick_successful: true,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ME弓MIMY或ME"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::I, Column::M),
+///             dest: Coord(Row::Y, Column::M),
+///             bridge_stick_size: None,
+///             bridge_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ME弓MIMY無"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::I, Column::M),
+///             dest: Coord(Row::Y, Column::M),
+///             bridge_stick_size: None,
+///             bridge_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ME弓MIMY無此無"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::I, Column::M),
+///             dest: Coord(Row::Y, Column::M),
+///             bridge_stick_size: Some(0),
+///             bridge_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("LY弓ZY無"),
+///     Ok((
+///         "",
+///         Move::NoStepAndWaterStick {
+///             src: Coord(Row::Y, Column::L),
+///             prof: Some(Profession::Gua2),
+///             dest: Coord(Row::Y, Column::Z),
+///             water_stick_size: None,
+///             water_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("LY弓ZY此無"),
+///     Ok((
+///         "",
+///         Move::NoStepAndWaterStick {
+///             src: Coord(Row::Y, Column::L),
+///             prof: Some(Profession::Gua2),
+///             dest: Coord(Row::Y, Column::Z),
+///             water_stick_size: None,
+///             water_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("LY弓ZY一LY"),
+///     Ok((
+///         "",
+///         Move::NoStepAndWaterStick {
+///             src: Coord(Row::Y, Column::L),
+///             prof: Some(Profession::Gua2),
+///             dest: Coord(Row::Y, Column::Z),
+///             water_stick_size: Some(1),
+///             water_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("LO弓NOCO四五"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStickAndWaterStick {
+///             src: Coord(Row::O, Column::L),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::O, Column::N),
+///             dest: Coord(Row::O, Column::C),
+///             bridge_stick_size: Some(4),
+///             water_stick_size: Some(5),
+///             water_stick_successful: true,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("或車CI"),
+///     Ok((
+///         "",
+///         Move::Parachute {
+///             color: None,
+///             prof: Profession::Kaun1,
+///             dest: Coord(Row::I, Column::C),
+///         }
+///     ))
+/// );
+/// ```
+pub fn parse(s: &str) -> IResult<&str, Move> {
+    alt((
+        movement::parse,
+        parse_parachute_of_unknown_color,
+        parse_abbreviated,
+    ))(s)
+}
+
+/// Parses a body element, with its movement elements written in notation ①.
+pub fn parse_body_elem(s: &str) -> IResult<&str, super::Elem> {
+    map(parse_body_elem_with_punctuation, |(elem, _)| elem)(s)
+}
+
+/// Parses a body element written in notation ①, keeping the punctuation mark that follows it.
+pub fn parse_body_elem_with_punctuation(
+    s: &str,
+) -> IResult<&str, (super::Elem, Option<super::Punctuation>)> {
+    alt((
+        |s| {
+            let (rest, (hand_creation, action)) = parse_ty_mok_ta_xot(s)?;
+            let (rest, punctuation) = super::parse_separator(rest)?;
+            Ok((
+                rest,
+                (super::Elem::TaXotTyMok(hand_creation, action), punctuation),
+            ))
+        },
+        |s| super::parse_body_elem_using(s, parse),
+    ))(s)
+}
+
+/// Parses `再行` or `終季` as written in notation ①, where the hand creation may come before it as a separate element,
+/// as in `[SY]為獣而手三 終季`, or be left out altogether, as in `再行` or `終季 手二十`.
+/// The points written after `而` are those of the hands, which follow from the hands, so they are not kept;
+/// the points of a taxot are those written after `終季`, if any.
+/// The form of notation ⓪ is also accepted.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation1::parse_ty_mok_ta_xot;
+/// use cetkaik_kiaak::body::{Action, HandCreation};
+/// use cetkaik_kiaak::body::hand::Hand;
+/// use std::collections::HashSet;
+/// use std::iter::FromIterator;
+/// let unknown = HandCreation { player_name: None, hands: None, points: None };
+/// assert_eq!(parse_ty_mok_ta_xot("再行"), Ok(("", (unknown.clone(), Action::TyMok))));
+/// assert_eq!(parse_ty_mok_ta_xot("終季 手二十"), Ok(("", (unknown.clone(), Action::TaXot(Some(20))))));
+/// assert_eq!(parse_ty_mok_ta_xot("終季\n手兵"), Ok(("\n手兵", (unknown, Action::TaXot(None)))));
+/// let marching = HandCreation {
+///     player_name: Some(String::from("SY")),
+///     hands: Some(HashSet::from_iter(vec![Hand::Marching].into_iter())),
+///     points: Some(5),
+/// };
+/// assert_eq!(
+///     parse_ty_mok_ta_xot("[SY]為行行而五\n終季"),
+///     Ok(("", (marching.clone(), Action::TaXot(None))))
+/// );
+/// assert_eq!(
+///     parse_ty_mok_ta_xot("[SY]為(行行)終季 手五"),
+///     Ok(("", (HandCreation { points: None, ..marching }, Action::TaXot(Some(5)))))
+/// );
+/// assert!(parse_ty_mok_ta_xot("[SY]為獣而手三").is_err());
+/// ```
+pub fn parse_ty_mok_ta_xot(s: &str) -> IResult<&str, (HandCreation, Action)> {
+    alt((super::parse_ty_mok_ta_xot, |s| {
+        let (rest, hand_creation) = opt(|s| {
+            let (rest, hand_creation) = parse_hand_creation(s)?;
+            let (rest, _) = super::parse_separator(rest)?;
+            Ok((rest, hand_creation))
+        })(s)?;
+        let (rest, action) = parse_action(rest)?;
+        let hand_creation = hand_creation.unwrap_or(HandCreation {
+            player_name: None,
+            hands: None,
+            points: None,
+        });
+        Ok((rest, (hand_creation, action)))
+    }))(s)
+}
+
+/// Parses a hand creation such as `[SY]為獣而手三` or `[SY]為行行而五`, where `手` may be left out, keeping the points of the hands.
+/// A hand may also be written in parentheses as in notation ⓪.
+fn parse_hand_creation(s: &str) -> IResult<&str, HandCreation> {
+    let (rest, player_name) =
+        super::super::parse_braced_string_without_trailing_spaces(s, '[', ']')?;
+    let (rest, _) = char('為')(rest)?;
+    let (rest, names) = many1(alt((
+        |s| super::super::parse_braced_string_without_trailing_spaces(s, '(', ')'),
+        parse_hand_name,
+    )))(rest)?;
+    let hands = super::read_hand_names(&names)
+        .ok_or_else(|| Err::Error(Error::new(rest, ErrorKind::Verify)))?;
+    let (rest, _) = char('而')(rest)?;
+    let (rest, _) = opt(char('手'))(rest)?;
+    let (rest, points) = super::super::parse_numeral(rest)?;
+    Ok((
+        rest,
+        HandCreation {
+            player_name: Some(player_name.to_owned()),
+            hands: Some(hands),
+            points: Some(points),
+        },
+    ))
+}
+
+/// The longest name of a hand that the input begins with, such as `同色獣` rather than `同色`.
+fn parse_hand_name(s: &str) -> IResult<&str, &str> {
+    let name = ALL_HANDS
+        .iter()
+        .map(|hand| hand.kanji())
+        .chain(std::iter::once("同色王"))
+        .filter(|name| s.starts_with(name))
+        .max_by_key(|name| name.len())
+        .ok_or_else(|| Err::Error(Error::new(s, ErrorKind::Tag)))?;
+    Ok((&s[name.len()..], name))
+}
+
+/// `再行`, or `終季` followed by the points if they are written.
+fn parse_action(s: &str) -> IResult<&str, Action> {
+    alt((map(tag("再行"), |_| Action::TyMok), |s| {
+        let (rest, _) = tag("終季")(s)?;
+        let (rest, points) = opt(preceded(
+            many0(one_of("\t\r\n \u{00a0}\u{3000}")),
+            preceded(char('手'), super::super::parse_numeral),
+        ))(rest)?;
+        Ok((rest, Action::TaXot(points)))
+    }))(s)
+}
+
+/// `或車CI` leaves the colour to be worked out from the hand.
+fn parse_parachute_of_unknown_color(s: &str) -> IResult<&str, Move> {
+    let (rem, _) = tag("或")(s)?;
+    let (rem, prof) = parse_profession(rem)?;
+    let (rem, dest) = parse_square(rem)?;
+    Ok((
+        rem,
+        Move::Parachute {
+            color: None,
+            prof,
+            dest,
+        },
+    ))
+}
+
+fn parse_abbreviated(s: &str) -> IResult<&str, Move> {
+    let (rem, src) = parse_square(s)?;
+    let (rem, prof) = parse_profession_or_wildcard(rem)?;
+    let (rem, first) = parse_square(rem)?;
+    let (rem, second) = opt(parse_square)(rem)?;
+    /* sticks that do not fit the move are reported where they begin */
+    let sticks_at = rem;
+    let invalid_sticks =
+        |rem| Err::Error(Error::new(rem, ErrorKind::Verify).expecting(sticks_at, Expected::Stick));
+    let (rem, sticks) = many_m_n(0, 2, parse_stick_value)(rem)?;
+    /* a failed move may be marked either by `此無` or by the square the piece returned to */
+    let (rem, fail) = opt(alt((map(tag("此無"), |_| None), map(parse_square, Some))))(rem)?;
+    let fail = match fail {
+        None => false,
+        Some(None) => true,
+        Some(Some(returned_to)) if returned_to == src => true,
+        Some(Some(_)) => return Err(invalid_sticks(rem)),
+    };
+
+    /* `LY弓ZY此無` is a failure whose value is unknown */
+    let sticks = if sticks.is_empty() && fail {
+        vec![StickValue::Unknown]
+    } else {
+        sticks
+    };
+
+    let movement = match second {
+        None => no_step_move(src, prof, first, &sticks, fail),
+        Some(dest) => step_move(src, prof, first, dest, &sticks, fail),
+    }
+    .ok_or_else(|| invalid_sticks(rem))?;
+
+    Ok((rem, movement))
+}
+
+pub(super) fn no_step_move(
+    src: absolute::Coord,
+    prof: PossiblyUnknown<Profession>,
+    dest: absolute::Coord,
+    sticks: &[StickValue],
+    fail: bool,
+) -> Option<Move> {
+    match sticks {
+        [] if is_water_entry(src, prof, dest) => Some(Move::NoStepAndWaterStick {
+            src,
+            prof,
+            dest,
+            water_stick_size: None,
+            water_stick_successful: true,
+        }),
+        [] => Some(Move::NoStepAndNoStick { src, prof, dest }),
+        [v] if is_water_entry(src, prof, dest) => {
+            let (water_stick_size, water_stick_successful) = interpret_water_stick(*v, fail)?;
+            Some(Move::NoStepAndWaterStick {
+                src,
+                prof,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn step_move(
+    src: absolute::Coord,
+    prof: PossiblyUnknown<Profession>,
+    step: absolute::Coord,
+    dest: absolute::Coord,
+    sticks: &[StickValue],
+    fail: bool,
+) -> Option<Move> {
+    match sticks {
+        [] if requires_bridge_stick(prof) => Some(Move::StepAndBridgeStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size: None,
+            bridge_stick_successful: true,
+        }),
+        _ => step_move_as_written(src, prof, step, dest, sticks, fail),
+    }
+}
+
+/// A stepping move with the sticks exactly as written, for a notation that always writes the sticks cast.
+/// Unlike in notation ①, a step without sticks casts none.
+pub(super) fn step_move_as_written(
+    src: absolute::Coord,
+    prof: PossiblyUnknown<Profession>,
+    step: absolute::Coord,
+    dest: absolute::Coord,
+    sticks: &[StickValue],
+    fail: bool,
+) -> Option<Move> {
+    match sticks {
+        [] if is_water_entry(src, prof, dest) => Some(Move::StepAndWaterStick {
+            src,
+            prof,
+            step,
+            dest,
+            water_stick_size: None,
+            water_stick_successful: true,
+        }),
+        [] => Some(Move::StepAndNoStick {
+            src,
+            prof,
+            step,
+            dest,
+        }),
+        [v] if !requires_bridge_stick(prof) && is_water_entry(src, prof, dest) => {
+            let (water_stick_size, water_stick_successful) = interpret_water_stick(*v, fail)?;
+            Some(Move::StepAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            })
+        }
+        [v] => {
+            let (bridge_stick_size, bridge_stick_successful) = interpret_bridge_stick(*v, fail);
+            Some(Move::StepAndBridgeStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_size,
+                bridge_stick_successful,
+            })
+        }
+        [b, w] if is_water_entry(src, prof, dest) => {
+            let bridge_stick_size = match b {
+                StickValue::Unknown => None,
+                StickValue::Known(n) => Some(*n),
+                StickValue::Failed => return None,
+            };
+            let (water_stick_size, water_stick_successful) = interpret_water_stick(*w, fail)?;
+            Some(Move::StepAndBridgeStickAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_size,
+                water_stick_size,
+                water_stick_successful,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// In notation ①, a lone `無` means a failed judgement with an unknown value, while `無此無` means zero.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub(super) enum StickValue {
+    Unknown,
+    Failed,
+    Known(i32),
+}
+
+fn parse_stick_value(s: &str) -> IResult<&str, StickValue> {
+    let (rem, c) = one_of("或無一二三四五")(s)?;
+    let value = match c {
+        '或' => StickValue::Unknown,
+        '無' => {
+            /* `無此無` is zero; a `無` not followed by `此無` is a failure of unknown value */
+            if rem.starts_with("此無") {
+                StickValue::Known(0)
+            } else {
+                StickValue::Failed
+            }
+        }
+        '一' => StickValue::Known(1),
+        '二' => StickValue::Known(2),
+        '三' => StickValue::Known(3),
+        '四' => StickValue::Known(4),
+        '五' => StickValue::Known(5),
+        _ => unreachable!(),
+    };
+    Ok((rem, value))
+}
+
+pub(super) fn interpret_water_stick(
+    v: StickValue,
+    fail: bool,
+) -> Option<(PossiblyUnknown<i32>, bool)> {
+    match v {
+        StickValue::Unknown => Some((None, !fail)),
+        StickValue::Failed => Some((None, false)),
+        StickValue::Known(n @ 0..=2) => Some((Some(n), false)),
+        StickValue::Known(n) if !fail => Some((Some(n), true)),
+        StickValue::Known(_) => None, /* three or more cannot fail */
+    }
+}
+
+fn interpret_bridge_stick(v: StickValue, fail: bool) -> (PossiblyUnknown<i32>, bool) {
+    match v {
+        StickValue::Unknown => (None, !fail),
+        StickValue::Failed => (None, false),
+        StickValue::Known(n) => (Some(n), !fail),
+    }
+}
+
+/// Whether the move tries to enter the water from outside, which requires a water stick unless the piece is a vessel.
+pub(super) fn is_water_entry(
+    src: absolute::Coord,
+    prof: PossiblyUnknown<Profession>,
+    dest: absolute::Coord,
+) -> bool {
+    prof != Some(Profession::Nuak1) && !absolute::is_water(src) && absolute::is_water(dest)
+}
+
+/// Notation ① does not say whether a stepping move needed a bridge stick.
+/// Under the standard rule, pieces that can move indefinitely need one, so that is what is assumed here.
+pub(super) fn requires_bridge_stick(prof: PossiblyUnknown<Profession>) -> bool {
+    matches!(
+        prof,
+        Some(Profession::Gua2 | Profession::Kua2 | Profession::Tuk2 | Profession::Nuak1)
+    )
+}
@@ -0,0 +1,342 @@
+use super::hand::Hand;
+use super::movement::{parse_square, Move};
+use super::notation1::{no_step_move, step_move_as_written, StickValue};
+use super::{Action, Elem, HandCreation};
+use crate::error::IResult;
+use crate::error::{expect, Error, Expected};
+use cetkaik_core::Profession;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::one_of;
+use nom::combinator::{eof, map, opt};
+use nom::error::ErrorKind;
+use nom::multi::{many0, many1};
+use nom::sequence::preceded;
+use nom::Err;
+
+/// Parses a profession written in its Pekzep pronunciation.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation2::parse_profession;
+/// use cetkaik_core::Profession;
+/// assert_eq!(parse_profession("nuak1"), Ok(("", Profession::Nuak1)));
+/// assert_eq!(parse_profession("kaun1"), Ok(("", Profession::Kaun1)));
+/// assert_eq!(parse_profession("io"), Ok(("", Profession::Io)))
+/// ```
+///
+pub fn parse_profession(s: &str) -> IResult<&str, Profession> {
+    alt((
+        map(tag("nuak1"), |_| Profession::Nuak1),
+        map(tag("kauk2"), |_| Profession::Kauk2),
+        map(tag("gua2"), |_| Profession::Gua2),
+        map(tag("kaun1"), |_| Profession::Kaun1),
+        map(tag("dau2"), |_| Profession::Dau2),
+        map(tag("maun1"), |_| Profession::Maun1),
+        map(tag("kua2"), |_| Profession::Kua2),
+        map(tag("tuk2"), |_| Profession::Tuk2),
+        map(tag("uai1"), |_| Profession::Uai1),
+        map(tag("io"), |_| Profession::Io),
+    ))(s)
+}
+
+fn parse_profession_or_tam(s: &str) -> IResult<&str, Option<Profession>> {
+    alt((map(tag("tam2"), |_| None), map(parse_profession, Some)))(s)
+}
+
+fn parse_color(s: &str) -> IResult<&str, cetkaik_core::Color> {
+    alt((
+        map(tag("kok1"), |_| cetkaik_core::Color::Kok1),
+        map(tag("huok2"), |_| cetkaik_core::Color::Huok2),
+    ))(s)
+}
+
+/// Converts a single Pekzep numeral syllable into the character used in the kanji transcription.
+fn parse_numeral_syllable(s: &str) -> IResult<&str, char> {
+    alt((
+        map(tag("mun1"), |_| '無'),
+        map(tag("ut2"), |_| '下'),
+        map(tag("et2"), |_| '一'),
+        map(tag("ik2"), |_| '二'),
+        map(alt((tag("om2"), tag("om1"))), |_| '三'),
+        map(tag("ap1"), |_| '四'),
+        map(tag("un1"), |_| '五'),
+        map(tag("net2"), |_| '六'),
+        map(tag("nik2"), |_| '七'),
+        map(alt((tag("nom2"), tag("nom1"))), |_| '八'),
+        map(tag("nap1"), |_| '九'),
+        map(tag("nun1"), |_| '十'),
+        map(tag("kit1"), |_| '百'),
+        map(tag("ue1"), |_| '万'),
+    ))(s)
+}
+
+/// Parses a numeral written as space-separated Pekzep syllables, such as `ik2 nun1` for 20.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation2::parse_numeral;
+/// assert_eq!(parse_numeral("om2"), Ok(("", 3)));
+/// assert_eq!(parse_numeral("ik2 nun1"), Ok(("", 20)));
+/// assert_eq!(parse_numeral("ut2 un1"), Ok(("", -5)))
+/// ```
+///
+pub fn parse_numeral(s: &str) -> IResult<&str, i64> {
+    let (rest, first) = parse_numeral_syllable(s)?;
+    let (rest, others) = many0(preceded(spaces, parse_numeral_syllable))(rest)?;
+    let chars: Vec<char> = std::iter::once(first).chain(others).collect();
+    match pekzep_numeral::chars_to_num(&chars) {
+        Some(n) => Ok((rest, n)),
+        None => Err(Err::Error(Error::new(rest, ErrorKind::Verify))), /* unparsable pekzep numeral */
+    }
+}
+
+fn parse_stick_value(s: &str) -> IResult<&str, StickValue> {
+    alt((
+        map(tag("mun1"), |_| StickValue::Failed),
+        map(tag("et2"), |_| StickValue::Known(1)),
+        map(tag("ik2"), |_| StickValue::Known(2)),
+        map(alt((tag("om2"), tag("om1"))), |_| StickValue::Known(3)),
+        map(tag("ap1"), |_| StickValue::Known(4)),
+        map(tag("un1"), |_| StickValue::Known(5)),
+    ))(s)
+}
+
+fn spaces(s: &str) -> IResult<&str, ()> {
+    let (rest, _) = many1(one_of("\t \u{00a0}\u{3000}"))(s)?;
+    Ok((rest, ()))
+}
+
+/// Parses a movement element written in notation ②, such as `TU dau2 XY` or `LE gua2 TE ZE ap1`.
+/// The sticks are interpreted in the same way as in notation ①, except that a step without sticks casts none,
+/// as in `MU gua2 MY MIA`, since notation ② always writes the sticks cast.
+/// A Tam2 followed by two squares steps on the first, as in `tam-step-unspecified` of notation ⓪:
+/// as for any other piece, the square between the source and the destination is the one stepped on,
+/// and notation ② has no brackets for a square where the Tam2 merely stops, like `[LE]` in `KE皇[LE]KI`.
+/// This is also how `ZO tam2 ZU XI` of the README has to be read, since ZU holds a vessel at that point.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation2::parse;
+/// use cetkaik_kiaak::body::movement::Move;
+/// use cetkaik_core::Profession;
+/// use cetkaik_core::absolute::*;
+/// assert_eq!(
+///     parse("TU dau2 XY"),
+///     Ok((
+///         "",
+///         Move::NoStepAndNoStick {
+///             src: Coord(Row::U, Column::T),
+///             prof: Some(Profession::Dau2),
+///             dest: Coord(Row::Y, Column::X),
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("LE gua2 TE ZE ap1"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::E, Column::L),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::E, Column::T),
+///             dest: Coord(Row::E, Column::Z),
+///             bridge_stick_size: Some(4),
+///             bridge_stick_successful: true,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("CIA kaun1 XAU ZAI et2"),
+///     Ok((
+///         "",
+///         Move::StepAndWaterStick {
+///             src: Coord(Row::IA, Column::C),
+///             prof: Some(Profession::Kaun1),
+///             step: Coord(Row::AU, Column::X),
+///             dest: Coord(Row::AI, Column::Z),
+///             water_stick_size: Some(1),
+///             water_stick_successful: false,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("ZO tam2 ZU XI"),
+///     Ok((
+///         "",
+///         Move::TamStepUnspecified {
+///             src: Coord(Row::O, Column::Z),
+///             step: Coord(Row::U, Column::Z),
+///             second_dest: Coord(Row::I, Column::X),
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse("kok1 kaun1 CI"),
+///     Ok((
+///         "",
+///         Move::Parachute {
+///             color: Some(cetkaik_core::Color::Kok1),
+///             prof: Profession::Kaun1,
+///             dest: Coord(Row::I, Column::C),
+///         }
+///     ))
+/// );
+/// ```
+pub fn parse(s: &str) -> IResult<&str, Move> {
+    alt((parse_parachute, parse_non_parachute))(s)
+}
+
+fn parse_parachute(s: &str) -> IResult<&str, Move> {
+    let (rem, color) = parse_color(s)?;
+    let (rem, prof) = preceded(spaces, parse_profession)(rem)?;
+    let (rem, dest) = preceded(spaces, parse_square)(rem)?;
+    Ok((
+        rem,
+        Move::Parachute {
+            color: Some(color),
+            prof,
+            dest,
+        },
+    ))
+}
+
+fn parse_non_parachute(s: &str) -> IResult<&str, Move> {
+    let (rem, src) = parse_square(s)?;
+    let (rem, prof) = preceded(spaces, parse_profession_or_tam)(rem)?;
+    let (rem, first) = preceded(spaces, parse_square)(rem)?;
+    let (rem, second) = opt(preceded(spaces, parse_square))(rem)?;
+
+    if prof.is_none() {
+        let movement = match second {
+            None => Move::TamNoStep {
+                src,
+                first_dest: None,
+                second_dest: first,
+            },
+            Some(second_dest) => Move::TamStepUnspecified {
+                src,
+                step: first,
+                second_dest,
+            },
+        };
+        return Ok((rem, movement));
+    }
+
+    let (rem, sticks) = many0(preceded(spaces, parse_stick_value))(rem)?;
+
+    /* a failed move may be marked by the square the piece returned to */
+    let (rem, returned_to) = opt(preceded(spaces, parse_square))(rem)?;
+    let fail = match returned_to {
+        None => false,
+        Some(returned_to) if returned_to == src => true,
+        Some(_) => return Err(Err::Error(Error::new(rem, ErrorKind::Verify))),
+    };
+
+    let movement = match second {
+        None => no_step_move(src, prof, first, &sticks, fail),
+        Some(dest) => step_move_as_written(src, prof, first, dest, &sticks, fail),
+    }
+    .ok_or_else(|| Err::Error(Error::new(rem, ErrorKind::Verify)))?;
+
+    Ok((rem, movement))
+}
+
+/// Parses a hand creation such as `[hsjoihs] zau io hop1 om2`, keeping the points of the hands if given.
+/// A hand is named either by its Pekzep name, which is only known for `io`, or by its name in notation ⓪.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation2::parse_hand_creation;
+/// use cetkaik_kiaak::body::HandCreation;
+/// use cetkaik_kiaak::body::hand::Hand;
+/// use std::collections::HashSet;
+/// use std::iter::FromIterator;
+/// assert_eq!(
+///     parse_hand_creation("[hsjoihs] zau io hop1 om2"),
+///     Ok((
+///         "",
+///         HandCreation {
+///             player_name: Some(String::from("hsjoihs")),
+///             hands: Some(HashSet::from_iter(vec![Hand::King].into_iter())),
+///             points: Some(3)
+///         }
+///     ))
+/// );
+/// ```
+pub fn parse_hand_creation(s: &str) -> IResult<&str, HandCreation> {
+    let (rest, player_name) =
+        super::super::parse_braced_string_without_trailing_spaces(s, '[', ']')?;
+    let (rest, _) = opt(spaces)(rest)?;
+    let (rest, _) = tag("zau")(rest)?;
+    let (rest, hands) = many1(preceded(spaces, parse_hand_name))(rest)?;
+    let (rest, points) = opt(preceded(
+        spaces,
+        preceded(tag("hop1"), preceded(spaces, parse_numeral)),
+    ))(rest)?;
+
+    Ok((
+        rest,
+        HandCreation {
+            player_name: Some(player_name.to_owned()),
+            hands: Some(hands.into_iter().collect()),
+            points,
+        },
+    ))
+}
+
+fn parse_hand_name(s: &str) -> IResult<&str, Hand> {
+    let (rest, name) = take_while1(|c: char| c.is_alphanumeric())(s)?;
+    let hand = match name {
+        "io" => Some(Hand::King),
+        name => Hand::from_kanji(name),
+    };
+    let hand = hand.ok_or_else(|| Err::Error(Error::new(s, ErrorKind::Verify)))?;
+    Ok((rest, hand))
+}
+
+/// Parses `ta xot1` or `ty mok1`.
+pub fn parse_action_word(s: &str) -> IResult<&str, bool> {
+    alt((
+        map(preceded(tag("ta"), preceded(spaces, tag("xot1"))), |_| true),
+        map(preceded(tag("ty"), preceded(spaces, tag("mok1"))), |_| {
+            false
+        }),
+    ))(s)
+}
+
+/// Elements are usually separated by periods, but other punctuation and newlines are also allowed.
+fn separator(s: &str) -> IResult<&str, ()> {
+    let (rest, _) = opt(spaces)(s)?;
+    let (rest, ()) = expect(
+        Expected::Separator,
+        alt((map(one_of(".,、。，．｡､\r\n"), |_| ()), map(eof, |_| ()))),
+    )(rest)?;
+    let (rest, ()) = super::super::skip_spaces_and_newlines(rest)?;
+    Ok((rest, ()))
+}
+
+/// Parses a body element written in notation ②.
+/// A hand creation and the following `ta xot1` or `ty mok1` together make up a single element.
+pub fn parse_body_elem(s: &str) -> IResult<&str, Elem> {
+    alt((
+        |s| {
+            let (rest, m) = parse(s)?;
+            let (rest, ()) = separator(rest)?;
+            Ok((rest, Elem::Move(m, None)))
+        },
+        |s| {
+            let (rest, hand_creation) = parse_hand_creation(s)?;
+            let (rest, ()) = separator(rest)?;
+            let (rest, is_ta_xot) = parse_action_word(rest)?;
+            let (rest, ()) = separator(rest)?;
+            /* notation ② writes the points of the hands, but not what the taxot pays out */
+            let action = if is_ta_xot {
+                Action::TaXot(None)
+            } else {
+                Action::TyMok
+            };
+            Ok((rest, Elem::TaXotTyMok(hand_creation, action)))
+        },
+    ))(s)
+}
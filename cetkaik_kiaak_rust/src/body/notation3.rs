@@ -0,0 +1,412 @@
+use super::hand::{Hand, ALL_HANDS};
+use super::movement::Move;
+use super::notation1::{
+    interpret_water_stick, is_water_entry, no_step_move, requires_bridge_stick, StickValue,
+};
+use super::{Action, Elem, HandCreation};
+use crate::error::Error;
+use crate::error::IResult;
+use cetkaik_core::{absolute, Color, Profession};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1};
+use nom::character::complete::{char, none_of, one_of};
+use nom::combinator::{eof, map, opt};
+use nom::error::ErrorKind;
+use nom::multi::{many0, many_m_n, separated_list1};
+use nom::sequence::preceded;
+use nom::Err;
+
+/// Parses a JSON string literal, returning its content with the escape sequences resolved.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation3::parse_string_literal;
+/// assert_eq!(parse_string_literal(r#""mauAmaimy1""#), Ok(("", String::from("mauAmaimy1"))));
+/// assert_eq!(parse_string_literal(r#""BVzi""#), Ok(("", String::from("BVzi"))));
+/// ```
+pub fn parse_string_literal(s: &str) -> IResult<&str, String> {
+    let (rest, _) = char('"')(s)?;
+    let (rest, chars) = many0(alt((none_of("\"\\"), parse_escape)))(rest)?;
+    let (rest, _) = char('"')(rest)?;
+    Ok((rest, chars.into_iter().collect()))
+}
+
+fn parse_escape(s: &str) -> IResult<&str, char> {
+    let (rest, _) = char('\\')(s)?;
+    let (rest, c) = one_of("\"\\/bfnrtu")(rest)?;
+    match c {
+        'b' => Ok((rest, '\u{0008}')),
+        'f' => Ok((rest, '\u{000c}')),
+        'n' => Ok((rest, '\n')),
+        'r' => Ok((rest, '\r')),
+        't' => Ok((rest, '\t')),
+        'u' => {
+            let (rest, digits) = many_m_n(4, 4, one_of("0123456789abcdefABCDEF"))(rest)?;
+            let code = u32::from_str_radix(&digits.into_iter().collect::<String>(), 16)
+                .expect("four hex digits always fit in u32");
+            match std::char::from_u32(code) {
+                Some(c) => Ok((rest, c)),
+                None => Err(Err::Error(Error::new(rest, ErrorKind::Verify))), /* lone surrogate */
+            }
+        }
+        c => Ok((rest, c)),
+    }
+}
+
+/// Parses a piece letter of MRF. `None` stands for Tam2.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation3::parse_piece;
+/// use cetkaik_core::Profession;
+/// assert_eq!(parse_piece("A"), Ok(("", Some(Profession::Gua2))));
+/// assert_eq!(parse_piece("M"), Ok(("", None)))
+/// ```
+///
+pub fn parse_piece(s: &str) -> IResult<&str, Option<Profession>> {
+    alt((
+        map(char('V'), |_| Some(Profession::Nuak1)),
+        map(char('P'), |_| Some(Profession::Kauk2)),
+        map(char('A'), |_| Some(Profession::Gua2)),
+        map(char('C'), |_| Some(Profession::Kaun1)),
+        map(char('T'), |_| Some(Profession::Dau2)),
+        map(char('H'), |_| Some(Profession::Maun1)),
+        map(char('O'), |_| Some(Profession::Kua2)),
+        map(char('S'), |_| Some(Profession::Tuk2)),
+        map(char('F'), |_| Some(Profession::Uai1)),
+        map(char('K'), |_| Some(Profession::Io)),
+        map(char('M'), |_| None),
+    ))(s)
+}
+
+fn parse_color(s: &str) -> IResult<&str, Color> {
+    alt((
+        map(char('B'), |_| Color::Huok2),
+        map(char('R'), |_| Color::Kok1),
+    ))(s)
+}
+
+/// Parses a square written in lowercase, such as `mau`.
+pub fn parse_square(s: &str) -> IResult<&str, absolute::Coord> {
+    let (rem, column) = one_of("klntzxcmp")(s)?;
+    let (rem, row) = many_m_n(1, 2, one_of("aeiouy"))(rem)?;
+
+    let coord = absolute::parse_coord(
+        &std::iter::once(column)
+            .chain(row)
+            .collect::<String>()
+            .to_uppercase(),
+    )
+    .ok_or_else(|| Err::Error(Error::new(rem, ErrorKind::Verify)))?;
+    Ok((rem, coord))
+}
+
+fn parse_stick(s: &str) -> IResult<&str, StickValue> {
+    let (rem, c) = one_of("012345")(s)?;
+    let n = match c {
+        '0' => 0,
+        '1' => 1,
+        '2' => 2,
+        '3' => 3,
+        '4' => 4,
+        '5' => 5,
+        _ => unreachable!(),
+    };
+    Ok((rem, StickValue::Known(n)))
+}
+
+/// Parses the content of a single MRF string, such as `mauAmaimy1` or `BVzi`.
+/// The sticks are interpreted in the same way as in notation ①, except that a step without sticks casts none,
+/// as in `pauScaucai`, since MRF always writes the sticks cast.
+/// A Tam2 followed by two squares steps on the first, as in `tam-step-unspecified` of notation ⓪:
+/// MRF has no way to write a Tam2 that stops on the way, so the middle square is always the one stepped on,
+/// like the pawn at NI in `zoMnine`.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation3::parse_movement;
+/// use cetkaik_kiaak::body::movement::Move;
+/// use cetkaik_core::Profession;
+/// use cetkaik_core::absolute::*;
+/// assert_eq!(
+///     parse_movement("mauAmaimy1"),
+///     Ok((
+///         "",
+///         Move::StepAndBridgeStick {
+///             src: Coord(Row::AU, Column::M),
+///             prof: Some(Profession::Gua2),
+///             step: Coord(Row::AI, Column::M),
+///             dest: Coord(Row::Y, Column::M),
+///             bridge_stick_size: Some(1),
+///             bridge_stick_successful: true,
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse_movement("pauScaucai"),
+///     Ok((
+///         "",
+///         Move::StepAndNoStick {
+///             src: Coord(Row::AU, Column::P),
+///             prof: Some(Profession::Tuk2),
+///             step: Coord(Row::AU, Column::C),
+///             dest: Coord(Row::AI, Column::C),
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse_movement("zoMnine"),
+///     Ok((
+///         "",
+///         Move::TamStepUnspecified {
+///             src: Coord(Row::O, Column::Z),
+///             step: Coord(Row::I, Column::N),
+///             second_dest: Coord(Row::E, Column::N),
+///         }
+///     ))
+/// );
+/// assert_eq!(
+///     parse_movement("BVzi"),
+///     Ok((
+///         "",
+///         Move::Parachute {
+///             color: Some(cetkaik_core::Color::Huok2),
+///             prof: Profession::Nuak1,
+///             dest: Coord(Row::I, Column::Z),
+///         }
+///     ))
+/// );
+/// ```
+pub fn parse_movement(s: &str) -> IResult<&str, Move> {
+    alt((parse_parachute, parse_non_parachute))(s)
+}
+
+fn parse_parachute(s: &str) -> IResult<&str, Move> {
+    let (rem, color) = parse_color(s)?;
+    let (rem, prof) = parse_piece(rem)?;
+    let prof = prof.ok_or_else(|| Err::Error(Error::new(rem, ErrorKind::Verify)))?; /* Tam2 is never in hand */
+    let (rem, dest) = parse_square(rem)?;
+    Ok((
+        rem,
+        Move::Parachute {
+            color: Some(color),
+            prof,
+            dest,
+        },
+    ))
+}
+
+fn parse_non_parachute(s: &str) -> IResult<&str, Move> {
+    let (rem, src) = parse_square(s)?;
+    let (rem, prof) = parse_piece(rem)?;
+    let (rem, first) = parse_square(rem)?;
+    let (rem, second) = opt(parse_square)(rem)?;
+
+    if prof.is_none() {
+        let movement = match second {
+            None => Move::TamNoStep {
+                src,
+                first_dest: None,
+                second_dest: first,
+            },
+            Some(second_dest) => Move::TamStepUnspecified {
+                src,
+                step: first,
+                second_dest,
+            },
+        };
+        return Ok((rem, movement));
+    }
+
+    let (rem, sticks) = many_m_n(0, 2, parse_stick)(rem)?;
+
+    let movement = match second {
+        None => no_step_move(src, prof, first, &sticks, false),
+        Some(dest) => step_move(src, prof, first, dest, &sticks),
+    }
+    .ok_or_else(|| Err::Error(Error::new(rem, ErrorKind::Verify)))?;
+
+    Ok((rem, movement))
+}
+
+/// A stepping move with the sticks that MRF writes after it.
+/// A single stick is the water stick of a piece that enters the water, unless the piece needs a bridge stick to step.
+fn step_move(
+    src: absolute::Coord,
+    prof: Option<Profession>,
+    step: absolute::Coord,
+    dest: absolute::Coord,
+    sticks: &[StickValue],
+) -> Option<Move> {
+    let water_entry = is_water_entry(src, prof, dest);
+    match *sticks {
+        [] => Some(Move::StepAndNoStick {
+            src,
+            prof,
+            step,
+            dest,
+        }),
+        [v] if water_entry && !requires_bridge_stick(prof) => {
+            let (water_stick_size, water_stick_successful) = interpret_water_stick(v, false)?;
+            Some(Move::StepAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            })
+        }
+        [StickValue::Known(n)] => Some(Move::StepAndBridgeStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size: Some(n),
+            bridge_stick_successful: true,
+        }),
+        [StickValue::Known(b), w] if water_entry => {
+            let (water_stick_size, water_stick_successful) = interpret_water_stick(w, false)?;
+            Some(Move::StepAndBridgeStickAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_size: Some(b),
+                water_stick_size,
+                water_stick_successful,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parses the `=tymor` or `=taxt=...` annotation that follows a move.
+/// MRF names neither the player nor the points, so both are left unknown, and so are the hands of `=tymor`.
+/// The hands listed after `=taxt=` are separated by `/`, each written as the letters of its pieces in any order.
+/// A further `F` in front marks a colour-matched hand, as in `FHT` for 同色獣; a hand without it is taken without the requirement on colour.
+/// The letters are first read as the pieces of a hand, so `FHCV` is 戦集, and `FFHCV` is 同色戦集.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation3::parse_hand_annotation;
+/// use cetkaik_kiaak::body::{Action, HandCreation};
+/// use cetkaik_kiaak::body::hand::Hand;
+/// use std::collections::HashSet;
+/// use std::iter::FromIterator;
+/// assert_eq!(
+///     parse_hand_annotation("=taxt=FHCV/HT"),
+///     Ok((
+///         "",
+///         (
+///             HandCreation {
+///                 player_name: None,
+///                 hands: Some(HashSet::from_iter(vec![Hand::WarGathering, Hand::Beasts].into_iter())),
+///                 points: None
+///             },
+///             Action::TaXot(None)
+///         )
+///     ))
+/// );
+/// assert_eq!(
+///     parse_hand_annotation("=taxt=FHT/K"),
+///     Ok((
+///         "",
+///         (
+///             HandCreation {
+///                 player_name: None,
+///                 hands: Some(HashSet::from_iter(vec![Hand::SameColorBeasts, Hand::King].into_iter())),
+///                 points: None
+///             },
+///             Action::TaXot(None)
+///         )
+///     ))
+/// );
+/// assert_eq!(
+///     parse_hand_annotation("=tymor"),
+///     Ok(("", (HandCreation { player_name: None, hands: None, points: None }, Action::TyMok)))
+/// );
+/// ```
+pub fn parse_hand_annotation(s: &str) -> IResult<&str, (HandCreation, Action)> {
+    let (rest, _) = char('=')(s)?;
+    let (rest, (hands, action)) = alt((
+        map(tag("tymor"), |_| (None, Action::TyMok)),
+        map(
+            preceded(tag("taxt="), separated_list1(char('/'), parse_hand)),
+            |hands| (Some(hands.into_iter().collect()), Action::TaXot(None)),
+        ),
+    ))(rest)?;
+    Ok((
+        rest,
+        (
+            HandCreation {
+                player_name: None,
+                hands,
+                points: None,
+            },
+            action,
+        ),
+    ))
+}
+
+fn parse_hand(s: &str) -> IResult<&str, Hand> {
+    let (rest, letters) = take_while1(|c: char| c.is_ascii_uppercase())(s)?;
+    /* `F` is also the letter of 将, but no hand is made of 将 and the pieces of another hand */
+    let hand = hand_of_letters(letters)
+        .or_else(|| {
+            letters
+                .strip_prefix('F')
+                .and_then(hand_of_letters)
+                .map(Hand::same_color)
+        })
+        .ok_or_else(|| Err::Error(Error::new(s, ErrorKind::Verify)))?;
+    Ok((rest, hand))
+}
+
+fn hand_of_letters(letters: &str) -> Option<Hand> {
+    let mut pieces = letters
+        .chars()
+        .map(|c| match parse_piece(&c.to_string()) {
+            Ok((_, Some(prof))) => Some(prof),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    pieces.sort_by_key(|prof| *prof as u8);
+    ALL_HANDS
+        .iter()
+        .copied()
+        .filter(|hand| !hand.is_same_color())
+        .find(|hand| {
+            let mut expected = hand.pieces().to_vec();
+            expected.sort_by_key(|prof| *prof as u8);
+            expected == pieces
+        })
+}
+
+fn parse_content(s: &str) -> IResult<&str, Vec<Elem>> {
+    let (rest, m) = parse_movement(s)?;
+    let (rest, annotation) = opt(parse_hand_annotation)(rest)?;
+    let (rest, _) = eof(rest)?;
+    let mut elems = vec![Elem::Move(m, None)];
+    if let Some((hand_creation, action)) = annotation {
+        elems.push(Elem::TaXotTyMok(hand_creation, action));
+    }
+    Ok((rest, elems))
+}
+
+/// Elements are separated by spaces, newlines, or punctuation.
+fn separator(s: &str) -> IResult<&str, ()> {
+    let (rest, ()) = super::super::skip_spaces_and_newlines(s)?;
+    let (rest, _) = opt(one_of(",、。，．｡､"))(rest)?;
+    let (rest, ()) = super::super::skip_spaces_and_newlines(rest)?;
+    Ok((rest, ()))
+}
+
+/// Parses a single MRF string literal. A move annotated with `=tymor` or `=taxt=` results in two elements.
+pub fn parse_body_elems(s: &str) -> IResult<&str, Vec<Elem>> {
+    let (rest, content) = parse_string_literal(s)?;
+    let (_, elems) =
+        parse_content(&content).map_err(|_| Err::Error(Error::new(s, ErrorKind::Verify)))?;
+    let (rest, ()) = separator(rest)?;
+    Ok((rest, elems))
+}
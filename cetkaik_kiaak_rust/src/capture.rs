@@ -0,0 +1,126 @@
+use super::body::movement::PossiblyUnknown;
+use super::body::{Body, Elem};
+use super::replay::{replay, ReplayError, State};
+use cetkaik_core::absolute::NonTam2Piece;
+use cetkaik_core::{serialize_color, serialize_prof, Color, Profession};
+use std::fmt;
+
+/// A disagreement between a capture comment such as `手黒馬` and what the preceding move did.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum CaptureIssue {
+    /// The capture comment does not come right after a move
+    NoPrecedingMove,
+
+    /// The preceding move captured nothing
+    NothingCaptured,
+
+    /// The capture comment names a piece other than the one captured.
+    /// A colour that is not written is not compared.
+    Mismatch {
+        written_color: PossiblyUnknown<Color>,
+        written_prof: Profession,
+        actual: NonTam2Piece,
+    },
+}
+
+/// An issue found in a body, together with the index of the capture comment in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct CaptureError {
+    pub index: usize,
+    pub issue: CaptureIssue,
+}
+
+impl fmt::Display for CaptureIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureIssue::NoPrecedingMove => write!(f, "no move comes right before it"),
+            CaptureIssue::NothingCaptured => write!(f, "the move before captures nothing"),
+            CaptureIssue::Mismatch {
+                written_color,
+                written_prof,
+                actual,
+            } => write!(
+                f,
+                "`手{}{}` is written, but the move before captures {}{}",
+                written_color.map_or("", serialize_color),
+                serialize_prof(*written_prof),
+                serialize_color(actual.color),
+                serialize_prof(actual.prof)
+            ),
+        }
+    }
+}
+
+/// Replays the body and checks every capture comment against the piece taken by the move right before it.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::capture::{check_capture_comments, CaptureError, CaptureIssue};
+/// use cetkaik_core::absolute::NonTam2Piece;
+/// use cetkaik_core::{Color, Profession};
+/// let (_, body) = parse_ckka("[SY] [JV]\nXI兵XU無撃裁 XAI兵XY無撃裁\nXU兵XY無撃裁 手赤兵\n").unwrap();
+/// assert_eq!(check_capture_comments(&body), Ok(vec![]));
+/// let (_, body) = parse_ckka("[SY] [JV]\nXI兵XU無撃裁 XAI兵XY無撃裁\nXU兵XY無撃裁 手黒兵\n").unwrap();
+/// assert_eq!(
+///     check_capture_comments(&body),
+///     Ok(vec![CaptureError {
+///         index: 3,
+///         issue: CaptureIssue::Mismatch {
+///             written_color: Some(Color::Huok2),
+///             written_prof: Profession::Kauk2,
+///             actual: NonTam2Piece { color: Color::Kok1, prof: Profession::Kauk2 },
+///         }
+///     }])
+/// );
+/// ```
+pub fn check_capture_comments(body: &Body) -> Result<Vec<CaptureError>, ReplayError> {
+    let mut errors = vec![];
+    let mut before = State::initial();
+
+    /* `Some(captured)` right after a move */
+    let mut last_move: Option<Option<NonTam2Piece>> = None;
+
+    for (index, (elem, state)) in body.elems().zip(replay(body)).enumerate() {
+        let state = state?;
+        match elem {
+            Elem::Move(..) => last_move = Some(captured(&before, &state)),
+            Elem::CaptureComment(color, prof) => {
+                let issue = match last_move.take() {
+                    None => Some(CaptureIssue::NoPrecedingMove),
+                    Some(None) => Some(CaptureIssue::NothingCaptured),
+                    Some(Some(actual))
+                        if actual.prof != *prof || color.is_some_and(|c| c != actual.color) =>
+                    {
+                        Some(CaptureIssue::Mismatch {
+                            written_color: *color,
+                            written_prof: *prof,
+                            actual,
+                        })
+                    }
+                    Some(Some(_)) => None,
+                };
+                errors.extend(issue.map(|issue| CaptureError { index, issue }));
+            }
+            Elem::TaXotTyMok(..) | Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => {
+                last_move = None;
+            }
+        }
+        before = state;
+    }
+    Ok(errors)
+}
+
+/// The piece that went into either hand between the two states. A captured piece is added at the end of the hand.
+fn captured(before: &State, after: &State) -> Option<NonTam2Piece> {
+    [
+        (&before.field.a_side_hop1zuo1, &after.field.a_side_hop1zuo1),
+        (
+            &before.field.ia_side_hop1zuo1,
+            &after.field.ia_side_hop1zuo1,
+        ),
+    ]
+    .iter()
+    .find(|(before, after)| after.len() > before.len())
+    .and_then(|(_, after)| after.last().copied())
+}
@@ -0,0 +1,377 @@
+use super::body::movement::{Move, PossiblyUnknown};
+use super::body::Body;
+use super::body::Elem;
+use cetkaik_core::absolute::{is_water, Coord};
+use cetkaik_core::Profession;
+use std::fmt;
+
+/// A way in which a movement element does not fit together even though it is syntactically fine.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Violation {
+    /// A water stick is cast by a piece that is already in the water
+    WaterStickFromWater,
+
+    /// A water stick is cast by a piece whose destination is not in the water
+    WaterStickToNonWater,
+
+    /// A vessel casts a water stick, which it never needs
+    VesselWithWaterStick,
+
+    /// A piece other than a vessel enters the water without casting a water stick
+    MissingWaterStick,
+
+    /// A water stick of 0 to 2 is said to succeed, or one of 3 to 5 is said to fail
+    InconsistentWaterStick,
+
+    /// A stick is outside the range of 0 to 5
+    StickOutOfRange,
+
+    /// A move without a step ends where it started
+    DestinationIsSource,
+
+    /// The stepped-on square is the square the piece leaves or arrives at
+    StepIsEndpoint,
+
+    /// A piece goes to a square that is on neither the same row, the same column, nor the same diagonal
+    NotInLine,
+}
+
+/// A violation found in a body, together with the index of the element in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct ConsistencyError {
+    pub index: usize,
+    pub violation: Violation,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::WaterStickFromWater => {
+                write!(f, "a water stick is cast by a piece already in the water")
+            }
+            Violation::WaterStickToNonWater => write!(
+                f,
+                "a water stick is cast by a piece that does not go into the water"
+            ),
+            Violation::VesselWithWaterStick => write!(f, "a vessel casts a water stick"),
+            Violation::MissingWaterStick => {
+                write!(f, "the piece goes into the water without a water stick")
+            }
+            Violation::InconsistentWaterStick => write!(
+                f,
+                "the water stick does not agree with whether the piece gets into the water"
+            ),
+            Violation::StickOutOfRange => write!(f, "a stick is outside 0 to 5"),
+            Violation::DestinationIsSource => write!(f, "the piece ends where it started"),
+            Violation::StepIsEndpoint => {
+                write!(f, "the piece steps on the square it leaves or arrives at")
+            }
+            Violation::NotInLine => write!(
+                f,
+                "the piece goes off the row, the column and the diagonals of where it was"
+            ),
+        }
+    }
+}
+
+/// Checks every movement element in the body, returning the violations in the order they appear.
+/// See `check_move_consistency` for what is checked.
+/// Whether the rules in use allow Tam2 to move twice in a row is left to `replay`, which knows who moved it.
+#[must_use]
+pub fn check_consistency(body: &Body) -> Vec<ConsistencyError> {
+    body.elems()
+        .enumerate()
+        .flat_map(|(index, elem)| match elem {
+            Elem::Move(m, _) => check_move_consistency(m)
+                .into_iter()
+                .map(|violation| ConsistencyError { index, violation })
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Checks whether the parts of a single move fit together: the sticks with the squares and the profession,
+/// and the squares with one another.
+/// The water stick is checked against the water squares `NO TO ZO XO CO ZI ZU ZY ZAI`:
+/// it is cast by a piece other than a vessel on entering them from outside, and only then.
+/// Since `片` may stand for a vessel, the restrictions on vessels are not checked for `片`.
+///
+/// Every piece, Tam2 included, moves along a row, a column or a diagonal, so each part of a move,
+/// from the source to the square stepped on or stopped at and from there on to the destination, is checked to do so.
+/// How far the piece may go depends on which side moves and on whether the piece stands in 皇処,
+/// neither of which the element tells, and whether the squares are occupied as the move needs is left to `replay`.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::movement::parse;
+/// use cetkaik_kiaak::consistency::{check_move_consistency, Violation};
+/// assert_eq!(check_move_consistency(&parse("LY弓ZY水五").unwrap().1), vec![]);
+/// assert_eq!(
+///     check_move_consistency(&parse("NO船ZO水五").unwrap().1),
+///     vec![Violation::WaterStickFromWater, Violation::VesselWithWaterStick]
+/// );
+/// assert_eq!(
+///     check_move_consistency(&parse("LY弓ZY無撃裁").unwrap().1),
+///     vec![Violation::MissingWaterStick]
+/// );
+/// assert_eq!(
+///     check_move_consistency(&parse("KE皇KEKA").unwrap().1),
+///     vec![Violation::StepIsEndpoint]
+/// );
+/// assert_eq!(
+///     check_move_consistency(&parse("LE弓TIZE橋四").unwrap().1),
+///     vec![Violation::NotInLine]
+/// );
+/// ```
+#[must_use]
+pub fn check_move_consistency(m: &Move) -> Vec<Violation> {
+    let mut violations = vec![];
+    match *m {
+        Move::NoStepAndNoStick { src, prof, dest } => {
+            check_no_step(&mut violations, src, dest);
+            check_lines(&mut violations, &[src, dest]);
+            check_no_water_stick(&mut violations, src, prof, dest);
+        }
+        Move::NoStepAndWaterStick {
+            src,
+            prof,
+            dest,
+            water_stick_size,
+            water_stick_successful,
+        } => {
+            check_no_step(&mut violations, src, dest);
+            check_lines(&mut violations, &[src, dest]);
+            check_water_stick(
+                &mut violations,
+                src,
+                prof,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            );
+        }
+        Move::StepAndNoStick {
+            src,
+            prof,
+            step,
+            dest,
+        } => {
+            check_step(&mut violations, src, step, dest);
+            check_lines(&mut violations, &[src, step, dest]);
+            check_no_water_stick(&mut violations, src, prof, dest);
+        }
+        Move::StepAndWaterStick {
+            src,
+            prof,
+            step,
+            dest,
+            water_stick_size,
+            water_stick_successful,
+        } => {
+            check_step(&mut violations, src, step, dest);
+            check_lines(&mut violations, &[src, step, dest]);
+            check_water_stick(
+                &mut violations,
+                src,
+                prof,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            );
+        }
+        Move::StepAndBridgeStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size,
+            bridge_stick_successful,
+        } => {
+            check_step(&mut violations, src, step, dest);
+            check_lines(&mut violations, &[src, step, dest]);
+            check_stick_range(&mut violations, bridge_stick_size);
+            /* a piece that failed the bridge stick never reaches the water */
+            if bridge_stick_successful {
+                check_no_water_stick(&mut violations, src, prof, dest);
+            }
+        }
+        Move::StepAndBridgeStickAndWaterStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size,
+            water_stick_size,
+            water_stick_successful,
+        } => {
+            check_step(&mut violations, src, step, dest);
+            check_lines(&mut violations, &[src, step, dest]);
+            check_stick_range(&mut violations, bridge_stick_size);
+            check_water_stick(
+                &mut violations,
+                src,
+                prof,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            );
+        }
+        /* Tam2 never casts a stick, and a parachute has no source square; only the squares are left to check */
+        Move::TamStepUnspecified { .. }
+        | Move::TamStepDuringFormer { .. }
+        | Move::TamStepDuringLatter { .. } => {
+            check_tam_step(&mut violations, m);
+            check_tam_lines(&mut violations, m);
+        }
+        Move::TamNoStep { .. } => check_tam_lines(&mut violations, m),
+        Move::Parachute { .. } => {}
+    }
+    violations
+}
+
+fn check_tam_step(violations: &mut Vec<Violation>, m: &Move) {
+    match *m {
+        Move::TamStepUnspecified {
+            src,
+            step,
+            second_dest,
+        } => check_step(violations, src, step, second_dest),
+        Move::TamStepDuringFormer {
+            src,
+            step,
+            first_dest,
+            ..
+        } => match first_dest {
+            Some(first_dest) => check_step(violations, src, step, first_dest),
+            None if step == src => violations.push(Violation::StepIsEndpoint),
+            None => {}
+        },
+        Move::TamStepDuringLatter {
+            first_dest,
+            step,
+            second_dest,
+            ..
+        } => match first_dest {
+            Some(first_dest) => check_step(violations, first_dest, step, second_dest),
+            None if step == second_dest => violations.push(Violation::StepIsEndpoint),
+            None => {}
+        },
+        _ => {}
+    }
+}
+
+/// The squares that Tam2 passes through in turn, where they are known.
+fn check_tam_lines(violations: &mut Vec<Violation>, m: &Move) {
+    let squares = match *m {
+        Move::TamNoStep {
+            src,
+            first_dest,
+            second_dest,
+        } => vec![Some(src), first_dest, Some(second_dest)],
+        Move::TamStepDuringFormer {
+            src,
+            step,
+            first_dest,
+            second_dest,
+        }
+        | Move::TamStepDuringLatter {
+            src,
+            step,
+            first_dest,
+            second_dest,
+        } => {
+            let mut squares = vec![Some(src), first_dest, Some(second_dest)];
+            let at = if matches!(m, Move::TamStepDuringFormer { .. }) {
+                1
+            } else {
+                2
+            };
+            squares.insert(at, Some(step));
+            squares
+        }
+        /* the step may come in either half, so no part of the path is known for `TamStepUnspecified` */
+        _ => return,
+    };
+    /* a square that is not known splits the path, since Tam2 may have gone anywhere in between */
+    for known in squares.split(Option::is_none) {
+        check_lines(
+            violations,
+            &known.iter().flatten().copied().collect::<Vec<_>>(),
+        );
+    }
+}
+
+/// Checks that the piece goes along a row, a column or a diagonal from each square to the next.
+fn check_lines(violations: &mut Vec<Violation>, squares: &[Coord]) {
+    if squares.windows(2).any(|pair| !in_line(pair[0], pair[1])) {
+        violations.push(Violation::NotInLine);
+    }
+}
+
+fn check_no_step(violations: &mut Vec<Violation>, src: Coord, dest: Coord) {
+    if src == dest {
+        violations.push(Violation::DestinationIsSource);
+    }
+}
+
+/// A stepping move may come back to where it started, as in `XAU虎CAIXAU橋三`.
+fn check_step(violations: &mut Vec<Violation>, src: Coord, step: Coord, dest: Coord) {
+    if step == src || step == dest {
+        violations.push(Violation::StepIsEndpoint);
+    }
+}
+
+/// Whether `to` is on the same row, the same column or the same diagonal as `from`.
+fn in_line(from: Coord, to: Coord) -> bool {
+    let Coord(from_row, from_column) = from;
+    let Coord(to_row, to_column) = to;
+    let rows = (to_row as i32 - from_row as i32).abs();
+    let columns = (to_column as i32 - from_column as i32).abs();
+    rows == 0 || columns == 0 || rows == columns
+}
+
+fn check_stick_range(violations: &mut Vec<Violation>, size: PossiblyUnknown<i32>) {
+    if let Some(size) = size {
+        if !(0..=5).contains(&size) {
+            violations.push(Violation::StickOutOfRange);
+        }
+    }
+}
+
+fn check_no_water_stick(
+    violations: &mut Vec<Violation>,
+    src: Coord,
+    prof: PossiblyUnknown<Profession>,
+    dest: Coord,
+) {
+    let is_non_vessel = prof.is_some_and(|p| p != Profession::Nuak1);
+    if is_non_vessel && !is_water(src) && is_water(dest) {
+        violations.push(Violation::MissingWaterStick);
+    }
+}
+
+fn check_water_stick(
+    violations: &mut Vec<Violation>,
+    src: Coord,
+    prof: PossiblyUnknown<Profession>,
+    dest: Coord,
+    size: PossiblyUnknown<i32>,
+    successful: bool,
+) {
+    if is_water(src) {
+        violations.push(Violation::WaterStickFromWater);
+    }
+    if !is_water(dest) {
+        violations.push(Violation::WaterStickToNonWater);
+    }
+    if prof == Some(Profession::Nuak1) {
+        violations.push(Violation::VesselWithWaterStick);
+    }
+    match size {
+        Some(0..=2) if successful => violations.push(Violation::InconsistentWaterStick),
+        Some(3..=5) if !successful => violations.push(Violation::InconsistentWaterStick),
+        Some(0..=5) | None => {}
+        Some(_) => violations.push(Violation::StickOutOfRange),
+    }
+}
@@ -0,0 +1,210 @@
+use super::body::hand::{hands_made, Hand};
+use super::body::{Body, Elem, HandCreation};
+use super::replay::{replay, ReplayError, State};
+use cetkaik_core::absolute::Side;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A disagreement between what `[player]為(...)` says and the hands actually made.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Issue {
+    /// A hand is declared, but the pieces in the hand of the player do not make it
+    NotMade(Hand),
+
+    /// A hand is declared that an earlier move made, but that was given up by not declaring it right after that move
+    GivenUp(Hand),
+
+    /// The move right before a declaration makes a new hand, but the declaration leaves it out
+    NotDeclared(Hand),
+
+    /// `再行` or `終季` is declared, but the move right before it makes no new hand
+    NoNewHand,
+}
+
+/// An issue found in a body, together with the index of the declaration in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct DeclarationError {
+    pub index: usize,
+    pub issue: Issue,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::NotMade(hand) => write!(
+                f,
+                "`{}` is declared, but the pieces in hand do not make it",
+                hand.kanji()
+            ),
+            Issue::GivenUp(hand) => write!(
+                f,
+                "`{}` was given up by not declaring it right after the move that made it",
+                hand.kanji()
+            ),
+            Issue::NotDeclared(hand) => write!(
+                f,
+                "the move before makes `{}`, but it is not declared",
+                hand.kanji()
+            ),
+            Issue::NoNewHand => write!(f, "the move before makes no new hand"),
+        }
+    }
+}
+
+/// Replays the body and checks every `[player]為(...)` against the pieces the player has captured so far.
+/// The player who declares is taken to be the one who made the last move.
+/// A new hand is declared right after the move that makes it, or not at all:
+/// a player who lets the next move come gives the hand up, which is not an issue until the hand is declared later.
+/// A hand declared again after `再行` is not an issue, as long as it is still made.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::declaration::check_declarations;
+/// let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+/// assert_eq!(check_declarations(&body), Ok(vec![]));
+/// ```
+pub fn check_declarations(body: &Body) -> Result<Vec<DeclarationError>, ReplayError> {
+    let mut errors = vec![];
+    let mut before = State::initial();
+    let mut declarations = Declarations::default();
+    for (index, (elem, state)) in body.elems().zip(replay(body)).enumerate() {
+        let state = state?;
+        if let Some(declaration) = declarations.update(elem, &before, &state) {
+            errors.extend(
+                declaration
+                    .issues
+                    .into_iter()
+                    .map(|issue| DeclarationError { index, issue }),
+            );
+        }
+        before = state;
+    }
+    Ok(errors)
+}
+
+/// A declaration as it is found in the replay.
+pub(crate) struct Declaration {
+    pub issues: Vec<Issue>,
+
+    /// The points of all the hands the player has declared in the season, and still has made,
+    /// except for the hands whose points the record writes
+    pub points: i64,
+
+    /// The points that the record writes for the hands the player has declared in the season, as in `而手三`
+    pub written_points: i64,
+}
+
+/// The hands each player has declared in the season, kept as the body is replayed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Declarations {
+    declared: [HashSet<Hand>; 2],
+
+    /* the points written for the hands so far in the season, and the hands they were written for */
+    written: [(i64, HashSet<Hand>); 2],
+
+    /* the hands made by the last move, which can only be declared before the next move */
+    new: HashSet<Hand>,
+}
+
+impl Declarations {
+    /// Takes in an element, given the states before and after it, and looks into it if it is a declaration.
+    pub(crate) fn update(
+        &mut self,
+        elem: &Elem,
+        before: &State,
+        after: &State,
+    ) -> Option<Declaration> {
+        let declaration = match elem {
+            Elem::Move(..) => {
+                self.new = match after.side_to_move.map(|side| !side) {
+                    Some(mover) => hands_of(after, mover)
+                        .difference(&hands_of(before, mover))
+                        .copied()
+                        .collect(),
+                    None => HashSet::new(),
+                };
+                None
+            }
+            Elem::TaXotTyMok(hand_creation, _) => Some(self.declare(hand_creation, before)),
+            Elem::CaptureComment(..) => None,
+            Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => {
+                self.new.clear();
+                None
+            }
+        };
+        if *after == State::initial() {
+            self.declared = [HashSet::new(), HashSet::new()];
+            self.written = [(0, HashSet::new()), (0, HashSet::new())];
+        }
+        declaration
+    }
+
+    fn declare(&mut self, hand_creation: &HandCreation, before: &State) -> Declaration {
+        /* before anyone has moved in the season, no hand can have been made */
+        let (mover, made) = match before.side_to_move {
+            Some(side) => (!side, hands_of(before, !side)),
+            None => (Side::ASide, HashSet::new()),
+        };
+        let new = std::mem::take(&mut self.new);
+        let already = &mut self.declared[side_index(mover)];
+        let mut issues = vec![];
+        /* where the notation leaves the hands out, only whether a new hand has been made can be checked */
+        let hands = hand_creation.hands.as_ref();
+        let given_up: HashSet<_> = hands.map_or_else(HashSet::new, |hands| {
+            hands
+                .iter()
+                .filter(|hand| {
+                    made.contains(hand) && !new.contains(hand) && !already.contains(hand)
+                })
+                .copied()
+                .collect()
+        });
+        if let Some(hands) = hands {
+            issues.extend(sorted(hands.difference(&made)).map(Issue::NotMade));
+            issues.extend(sorted(given_up.iter()).map(Issue::GivenUp));
+        }
+        if new.is_empty() {
+            issues.push(Issue::NoNewHand);
+        }
+        if let Some(hands) = hands {
+            issues.extend(sorted(new.difference(hands)).map(Issue::NotDeclared));
+        }
+        already.extend(hands.unwrap_or(&new).difference(&given_up));
+        let (written_points, written_hands) = &mut self.written[side_index(mover)];
+        if let Some(points) = hand_creation.points {
+            *written_points += points;
+            written_hands.extend(hands.unwrap_or(&new));
+        }
+        let points = already
+            .intersection(&made)
+            .filter(|hand| !written_hands.contains(hand))
+            .map(|hand| hand.points())
+            .sum();
+        Declaration {
+            issues,
+            points,
+            written_points: *written_points,
+        }
+    }
+}
+
+fn sorted<'a>(hands: impl Iterator<Item = &'a Hand>) -> impl Iterator<Item = Hand> {
+    let mut hands: Vec<_> = hands.copied().collect();
+    hands.sort();
+    hands.into_iter()
+}
+
+pub(crate) fn hands_of(state: &State, side: Side) -> HashSet<Hand> {
+    match side {
+        Side::ASide => hands_made(&state.field.a_side_hop1zuo1),
+        Side::IASide => hands_made(&state.field.ia_side_hop1zuo1),
+    }
+}
+
+const fn side_index(side: Side) -> usize {
+    match side {
+        Side::ASide => 0,
+        Side::IASide => 1,
+    }
+}
@@ -0,0 +1,342 @@
+use super::body::{Notation, Punctuation};
+use nom::character::complete::one_of;
+use nom::error::ErrorKind;
+use nom::multi::many_m_n;
+use nom::{Err, InputLength};
+use std::fmt;
+
+/// A position in the original input. Both are 1-based, and the column counts characters, not bytes.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What the parser was looking for when it gave up.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Expected {
+    /// `{...}` or `[player]`
+    HeaderElem,
+
+    /// The second `[player]` of the header
+    Player,
+
+    /// The bracket that closes the one opened before, together with the `#`s that opened it
+    ClosingBracket(char),
+
+    /// A movement element, `手`, `[player]為`, a season end, `星一周`, or a line of points
+    BodyElem,
+
+    /// A square such as `XU`
+    Square,
+
+    /// A profession such as `兵`
+    Profession,
+
+    /// A profession, `片`, or `皇`
+    ProfessionOrTam,
+
+    /// `無撃裁`, `橋`, or `水`
+    Stick,
+
+    /// `或`, `無`, or `一` to `五` after `橋`
+    BridgeStickSize,
+
+    /// The name of a standard hand, such as `獣`
+    Hand,
+
+    /// `再行` or `終季`
+    Action,
+
+    /// A numeral in either Arabic or Pekzep style
+    Numeral,
+
+    /// A fixed string
+    Literal(&'static str),
+
+    /// A valid water stick after `水`, which is reported as `ParseError::BadWaterStick`
+    WaterStick,
+
+    /// The rest of a body element written in the notation, where the parser names no narrower part
+    ElemIn(Notation),
+
+    /// Whitespace, a punctuation mark or the end of the input
+    Separator,
+}
+
+/// The error returned by `parse_ckka`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum ParseError {
+    /// The input begins with a byte order mark, which a CKKA file must not have
+    ByteOrderMark,
+
+    /// The input is not valid UTF-8; `position` is where the first invalid byte is
+    InvalidUtf8 { position: Position },
+
+    /// The header could not be read to its end
+    Header {
+        position: Position,
+        expected: Expected,
+    },
+
+    /// The body could not be read to its end. `fragment` is the element that could not be parsed.
+    UnparsedBodyFragment {
+        position: Position,
+        fragment: String,
+        expected: Expected,
+    },
+
+    /// `水` is followed by something that is not a valid water stick, such as `水三此無`
+    BadWaterStick {
+        position: Position,
+        fragment: String,
+    },
+}
+
+impl ParseError {
+    #[must_use]
+    pub const fn position(&self) -> Position {
+        match *self {
+            ParseError::ByteOrderMark => Position { line: 1, column: 1 },
+            ParseError::InvalidUtf8 { position }
+            | ParseError::Header { position, .. }
+            | ParseError::UnparsedBodyFragment { position, .. }
+            | ParseError::BadWaterStick { position, .. } => position,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Expected::HeaderElem => write!(f, "`{{...}}` or `[...]`"),
+            Expected::Player => write!(f, "the second player `[...]`"),
+            Expected::ClosingBracket(c) => write!(f, "`{c}`"),
+            Expected::BodyElem => write!(f, "a body element"),
+            Expected::Square => write!(f, "a square"),
+            Expected::Profession => write!(f, "a profession"),
+            Expected::ProfessionOrTam => write!(f, "a profession, `片` or `皇`"),
+            Expected::Stick => write!(f, "`無撃裁`, `橋` or `水`"),
+            Expected::BridgeStickSize => write!(f, "the value of the bridge stick"),
+            Expected::Hand => write!(f, "a hand"),
+            Expected::Action => write!(f, "`再行` or `終季`"),
+            Expected::Numeral => write!(f, "a numeral"),
+            Expected::Literal(s) => write!(f, "`{s}`"),
+            Expected::WaterStick => write!(f, "a valid water stick"),
+            Expected::ElemIn(notation) => {
+                let notation = match notation {
+                    Notation::Zero => '⓪',
+                    Notation::One => '①',
+                    Notation::Two => '②',
+                    Notation::Three => '③',
+                };
+                write!(f, "the rest of a body element in notation {notation}")
+            }
+            Expected::Separator => write!(f, "a space, a newline or a punctuation mark"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::ByteOrderMark => {
+                write!(f, "a CKKA file must not begin with a byte order mark")
+            }
+            ParseError::InvalidUtf8 { position } => write!(f, "{position}: invalid UTF-8"),
+            ParseError::Header { position, expected } => {
+                write!(f, "{position}: expected {expected} in the header")
+            }
+            ParseError::UnparsedBodyFragment {
+                position,
+                fragment,
+                expected,
+            } => write!(
+                f,
+                "{position}: expected {expected} while parsing `{fragment}` in the body"
+            ),
+            ParseError::BadWaterStick { position, fragment } => {
+                write!(f, "{position}: `{fragment}` is not a valid water stick")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The position of `rest`, which must be a suffix of `text`. `text` is assumed to begin at line `first_line`.
+pub(crate) fn position_of(text: &str, rest: &str, first_line: usize) -> Position {
+    let consumed = &text[..text.len() - rest.len()];
+    let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: first_line + consumed.matches('\n').count(),
+        column: consumed[line_start..].chars().count() + 1,
+    }
+}
+
+/// Works out why the header could not be read any further at `rest`.
+pub(crate) fn diagnose_header(rest: &str, only_one_player: bool) -> Expected {
+    if only_one_player {
+        return Expected::Player;
+    }
+    match rest.trim_start_matches('#').chars().next() {
+        Some('{') => Expected::ClosingBracket('}'),
+        Some('[') => Expected::ClosingBracket(']'),
+        _ => Expected::HeaderElem,
+    }
+}
+
+/// The element starting at `s`, up to the next whitespace or punctuation mark.
+pub(crate) fn fragment_at(s: &str) -> String {
+    s.split(|c: char| c.is_whitespace() || Punctuation::from_char(c).is_some())
+        .next()
+        .unwrap_or(s)
+        .to_owned()
+}
+
+/// The water stick starting at `s`, such as `水三此無`.
+pub(crate) fn water_stick_fragment(s: &str) -> String {
+    let stick: IResult<&str, Vec<char>> =
+        many_m_n(0, 3, one_of("或無一二三四五此"))(&s['水'.len_utf8()..]);
+    stick.map_or_else(
+        |_| String::from("水"),
+        |(_, chars)| std::iter::once('水').chain(chars).collect(),
+    )
+}
+
+/// Turns the error of the parser of the body element at `elem`, written in `notation`, into a `ParseError`.
+pub(crate) fn elem_error(
+    body: &str,
+    elem: &str,
+    error: &Error<&str>,
+    notation: Notation,
+    first_line: usize,
+) -> ParseError {
+    let position = position_of(body, error.input, first_line);
+    let expected = match error.expected {
+        Some(Expected::WaterStick) => {
+            return ParseError::BadWaterStick {
+                position,
+                fragment: water_stick_fragment(error.input),
+            }
+        }
+        /* nothing in the element could be read, whichever part was tried first */
+        _ if error.input.len() == elem.len() => Expected::BodyElem,
+        Some(expected) => expected,
+        None => Expected::ElemIn(notation),
+    };
+    ParseError::UnparsedBodyFragment {
+        position,
+        fragment: fragment_at(elem),
+        expected,
+    }
+}
+
+/// Turns a body that cannot be read to its end into an error, reported against the notation that read furthest.
+pub(crate) fn body_error(body: &str, first_line: usize) -> ParseError {
+    let (notation, elem, error) = super::body_failure(body);
+    elem_error(body, elem, &error, notation, first_line)
+}
+
+/// The error inside a nom error; an `Incomplete` is taken to be at the end of `whole`.
+pub(crate) fn error_of<'a>(e: Err<Error<&'a str>>, whole: &'a str) -> Error<&'a str> {
+    match e {
+        Err::Error(e) | Err::Failure(e) => e,
+        Err::Incomplete(_) => Error::new(&whole[whole.len()..], ErrorKind::Eof),
+    }
+}
+
+/// The result of the parsers of this crate.
+pub type IResult<I, O> = nom::IResult<I, O, Error<I>>;
+
+/// The error returned by the parsers of this crate, in place of `nom::error::Error`.
+/// When every alternative fails, the error of the one that read furthest is kept rather than that of the last one,
+/// and the part of the grammar that failed is named where the parser names it.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Error<I> {
+    /// Where the part that failed begins, or where the parser stopped if the part is not named
+    pub input: I,
+
+    /// What the part that failed is, if it is named
+    pub expected: Option<Expected>,
+
+    /// How much of the input is left where the parser stopped
+    remaining: usize,
+}
+
+impl<I: InputLength> Error<I> {
+    #[must_use]
+    pub fn new(input: I, _kind: ErrorKind) -> Self {
+        Error {
+            remaining: input.input_len(),
+            input,
+            expected: None,
+        }
+    }
+
+    /// Names the part that begins at `at` as what failed, unless a part inside it has been named already.
+    #[must_use]
+    pub fn expecting(self, at: I, expected: Expected) -> Self {
+        match self.expected {
+            Some(_) => self,
+            None => Error {
+                input: at,
+                expected: Some(expected),
+                remaining: self.remaining,
+            },
+        }
+    }
+
+    /// How much of the input is left where the parser stopped; the less is left, the further it read.
+    pub(crate) const fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<I: InputLength> nom::error::ParseError<I> for Error<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        Error::new(input, kind)
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    /// Keeps the error that read further; of two that read equally far, the later one is kept unless only the earlier is named.
+    fn or(self, other: Self) -> Self {
+        if other.remaining < self.remaining
+            || (other.remaining == self.remaining
+                && (other.expected.is_some() || self.expected.is_none()))
+        {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// Runs `parser`, naming it as `expected` if it fails without a part inside it having been named.
+pub(crate) fn expect<I: Clone + InputLength, O>(
+    expected: Expected,
+    mut parser: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, O> {
+    move |s: I| parser(s.clone()).map_err(|e| e.map(|e| e.expecting(s.clone(), expected)))
+}
+
+/// Like `opt`, except that an error that got past the beginning of the input is returned rather than taken
+/// to mean that nothing is there, since what has been read cannot be anything else.
+pub(crate) fn opt_unless_begun<I: Clone + InputLength, O>(
+    mut parser: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, Option<O>> {
+    move |s: I| match parser(s.clone()) {
+        Ok((rest, o)) => Ok((rest, Some(o))),
+        Err(Err::Error(e)) if e.remaining == s.input_len() => Ok((s, None)),
+        Err(e) => Err(e),
+    }
+}
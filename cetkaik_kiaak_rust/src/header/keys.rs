@@ -0,0 +1,264 @@
+use super::{Elem, Header};
+use crate::body::{parse_season, Season};
+use crate::error::IResult;
+use cetkaik_core::Color;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while1, take_while_m_n};
+use nom::character::complete::one_of;
+use nom::combinator::{eof, map, opt};
+use nom::sequence::preceded;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The rule set named by `{律:...}`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Rule {
+    /// 硬皇力
+    HardTam2Power,
+
+    /// A rule set that this crate does not know, kept by its name; a record under it cannot be checked
+    Other(String),
+}
+
+/// A date and time given in RFC 3339, such as `2020-10-14T19:39:05+09:00`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+
+    /// 60 only for a leap second
+    pub second: u8,
+
+    /// The fraction of a second, in nanoseconds
+    pub nanosecond: u32,
+
+    /// The offset from UTC in minutes; `Z` gives 0
+    pub offset_minutes: i16,
+}
+
+/// The value of a well-known key in the header cannot be read.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ValueError {
+    pub key: &'static str,
+    pub value: String,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot read `{}` as the value of `{}`",
+            self.value, self.key
+        )
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl Header {
+    /// The value of the first `{key:value}` with the given key. Keys that this crate does not know are looked up all the same.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::parse_ckka;
+    /// let (header, _) = parse_ckka("{律:硬皇力} {場所:渋谷}\n[SY] [JV]\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(header.get("場所"), Some("渋谷"));
+    /// assert_eq!(header.get("季"), None);
+    /// ```
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.info.iter().find_map(|elem| match elem {
+            Elem::KeyedValue(k, v) if k == key => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The rule set given by `{律:...}`. A name that this crate does not know is kept as `Rule::Other`.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::parse_ckka;
+    /// use cetkaik_kiaak::header::keys::Rule;
+    /// let (header, _) = parse_ckka("{律:硬皇力}\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(header.rule(), Some(Rule::HardTam2Power));
+    /// let (header, _) = parse_ckka("{律:軟皇力}\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(header.rule(), Some(Rule::Other(String::from("軟皇力"))));
+    /// ```
+    #[must_use]
+    pub fn rule(&self) -> Option<Rule> {
+        self.get("律").map(|name| match name {
+            "硬皇力" => Rule::HardTam2Power,
+            _ => Rule::Other(name.to_owned()),
+        })
+    }
+
+    /// The time the game started, given by `{始時:...}`.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::parse_ckka;
+    /// use cetkaik_kiaak::header::keys::DateTime;
+    /// let (header, _) = parse_ckka("{始時:2020-10-14T19:39:05+09:00}\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(
+    ///     header.start_time(),
+    ///     Ok(Some(DateTime {
+    ///         year: 2020,
+    ///         month: 10,
+    ///         day: 14,
+    ///         hour: 19,
+    ///         minute: 39,
+    ///         second: 5,
+    ///         nanosecond: 0,
+    ///         offset_minutes: 9 * 60
+    ///     }))
+    /// );
+    /// let (header, _) = parse_ckka("{始時:2020-02-30T19:39:05Z}\nXU兵XY無撃裁\n").unwrap();
+    /// assert!(header.start_time().is_err());
+    /// ```
+    pub fn start_time(&self) -> Result<Option<DateTime>, ValueError> {
+        self.read("始時", parse_date_time)
+    }
+
+    /// The name of the player of the given colour, given by `{黒名:...}` or `{赤名:...}`.
+    #[must_use]
+    pub fn player_name(&self, color: Color) -> Option<&str> {
+        match color {
+            Color::Huok2 => self.get("黒名"),
+            Color::Kok1 => self.get("赤名"),
+        }
+    }
+
+    /// The colour of the player who moves first, given by `{一位色:...}`.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::parse_ckka;
+    /// use cetkaik_core::Color;
+    /// let (header, _) = parse_ckka("{一位色:赤} {季:夏}\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(header.first_color(), Ok(Some(Color::Kok1)));
+    /// let (header, _) = parse_ckka("{一位色:青}\nXU兵XY無撃裁\n").unwrap();
+    /// assert!(header.first_color().is_err());
+    /// ```
+    pub fn first_color(&self) -> Result<Option<Color>, ValueError> {
+        self.read("一位色", |s| {
+            alt((
+                map(tag("赤"), |_| Color::Kok1),
+                map(tag("黒"), |_| Color::Huok2),
+            ))(s)
+        })
+    }
+
+    /// The season in which the game starts, given by `{季:...}`.
+    pub fn season(&self) -> Result<Option<Season>, ValueError> {
+        self.read("季", parse_season)
+    }
+
+    /// Reads the value of `key` with `parser`, which has to consume the whole value.
+    fn read<T>(
+        &self,
+        key: &'static str,
+        parser: impl Fn(&str) -> IResult<&str, T>,
+    ) -> Result<Option<T>, ValueError> {
+        self.get(key)
+            .map(|value| match parser(value) {
+                Ok(("", parsed)) => Ok(parsed),
+                _ => Err(ValueError {
+                    key,
+                    value: value.to_owned(),
+                }),
+            })
+            .transpose()
+    }
+}
+
+fn digits(n: usize) -> impl Fn(&str) -> IResult<&str, u32> {
+    move |s| {
+        let (rest, d) = take_while_m_n(n, n, |c: char| c.is_ascii_digit())(s)?;
+        Ok((rest, d.parse().expect("ASCII digits always parse")))
+    }
+}
+
+fn in_range(s: &str, n: u32, range: std::ops::RangeInclusive<u32>) -> IResult<&str, u8> {
+    if range.contains(&n) {
+        Ok((s, u8::try_from(n).expect("checked to be in range")))
+    } else {
+        Err(nom::Err::Error(crate::error::Error::new(
+            s,
+            nom::error::ErrorKind::Verify,
+        )))
+    }
+}
+
+const fn days_in_month(year: u32, month: u32) -> u32 {
+    let is_leap_year = matches!(
+        (year % 4, year % 100, year % 400),
+        (0, 1..=99, _) | (_, _, 0)
+    );
+    match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn parse_date_time(s: &str) -> IResult<&str, DateTime> {
+    let (rest, year) = digits(4)(s)?;
+    let (rest, _) = tag("-")(rest)?;
+    let (rest, month) = digits(2)(rest)?;
+    let (rest, month) = in_range(rest, month, 1..=12)?;
+    let (rest, _) = tag("-")(rest)?;
+    let (rest, day) = digits(2)(rest)?;
+    let (rest, day) = in_range(rest, day, 1..=days_in_month(year, u32::from(month)))?;
+    let (rest, _) = one_of("Tt ")(rest)?;
+    let (rest, hour) = digits(2)(rest)?;
+    let (rest, hour) = in_range(rest, hour, 0..=23)?;
+    let (rest, _) = tag(":")(rest)?;
+    let (rest, minute) = digits(2)(rest)?;
+    let (rest, minute) = in_range(rest, minute, 0..=59)?;
+    let (rest, _) = tag(":")(rest)?;
+    let (rest, second) = digits(2)(rest)?;
+    let (rest, second) = in_range(rest, second, 0..=60)?;
+    let (rest, fraction) = opt(preceded(
+        tag("."),
+        take_while1(|c: char| c.is_ascii_digit()),
+    ))(rest)?;
+    let (rest, offset_minutes) = parse_offset(rest)?;
+    let (rest, _) = eof(rest)?;
+
+    /* digits beyond nanoseconds are dropped */
+    let nanosecond = fraction.map_or(0, |fraction: &str| {
+        format!("{:0<9}", &fraction[..fraction.len().min(9)])
+            .parse()
+            .expect("nine ASCII digits always fit in u32")
+    });
+    Ok((
+        rest,
+        DateTime {
+            year: u16::try_from(year).expect("four digits always fit in u16"),
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset_minutes,
+        },
+    ))
+}
+
+fn parse_offset(s: &str) -> IResult<&str, i16> {
+    alt((map(one_of("Zz"), |_| 0), |s| {
+        let (rest, sign) = one_of("+-")(s)?;
+        let (rest, hour) = digits(2)(rest)?;
+        let (rest, hour) = in_range(rest, hour, 0..=23)?;
+        let (rest, _) = tag(":")(rest)?;
+        let (rest, minute) = digits(2)(rest)?;
+        let (rest, minute) = in_range(rest, minute, 0..=59)?;
+        let offset = i16::from(hour) * 60 + i16::from(minute);
+        Ok((rest, if sign == '-' { -offset } else { offset }))
+    }))(s)
+}
@@ -1,28 +1,39 @@
 #[warn(clippy::pedantic)]
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Header {
     pub info: Vec<Elem>,
     pub players: Option<(PlayerAndPoint, PlayerAndPoint)>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerAndPoint {
     pub player_name: String,
     pub point: i64,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "crate::json::HeaderElemRepr",
+        into = "crate::json::HeaderElemRepr"
+    )
+)]
 pub enum Elem {
     Value(String),
     KeyedValue(String, String),
 }
 
+use crate::error::Error;
+use crate::error::IResult;
 use nom::combinator::opt;
-use nom::error::{Error, ErrorKind};
+use nom::error::ErrorKind;
 use nom::multi::many0;
 use nom::multi::many_m_n;
 use nom::Err;
-use nom::IResult;
 
 fn elem_parser(s: &str) -> IResult<&str, Elem> {
     let (no_used, in_string) = super::parse_braced_string(s, '{', '}')?;
@@ -41,14 +52,14 @@ use super::skip_spaces_and_newlines;
 
 fn player_and_point_parser(s: &str) -> IResult<&str, (String, Option<i64>)> {
     let (no_used, player_name) = super::parse_braced_string(s, '[', ']')?;
-    let (no_used, _) = skip_spaces_and_newlines(no_used)?;
+    let (no_used, ()) = skip_spaces_and_newlines(no_used)?;
     let (no_used, opt_num) = opt(super::parse_numeral)(no_used)?;
-    let (no_used, _) = skip_spaces_and_newlines(no_used)?;
+    let (no_used, ()) = skip_spaces_and_newlines(no_used)?;
     Ok((no_used, (player_name.to_owned(), opt_num)))
 }
 
 pub fn parse(input: &str) -> IResult<&str, Header> {
-    let (no_used, _) = skip_spaces_and_newlines(input)?;
+    let (no_used, ()) = skip_spaces_and_newlines(input)?;
     let (no_used, info) = many0(elem_parser)(no_used)?;
     let (no_used, vec2) = many_m_n(0, 2, player_and_point_parser)(no_used)?;
     let players = match vec2.as_slice() {
@@ -80,5 +91,9 @@ pub fn parse(input: &str) -> IResult<&str, Header> {
     Ok((no_used, Header { info, players }))
 }
 
+pub mod keys;
+
 #[cfg(test)]
+/* the tests that came with the crate are kept as they were written */
+#[allow(clippy::needless_raw_string_hashes, clippy::semicolon_if_nothing_returned)]
 mod tests_;
@@ -145,3 +145,67 @@ fn it_works() {
         ))
     )
 }
+
+#[test]
+fn well_known_keys() {
+    use cetkaik_core::Color;
+    let (_, header) = parse("{黒名:kuai1} {赤名:iei2}\n{一位色:赤} {季:夏} {場所:渋谷}\n").unwrap();
+    assert_eq!(header.player_name(Color::Huok2), Some("kuai1"));
+    assert_eq!(header.player_name(Color::Kok1), Some("iei2"));
+    assert_eq!(header.first_color(), Ok(Some(Color::Kok1)));
+    assert_eq!(header.season(), Ok(Some(crate::body::Season::Summer)));
+    assert_eq!(header.rule(), None);
+    assert_eq!(header.start_time(), Ok(None));
+    assert_eq!(header.info.len(), 5);
+    assert_eq!(header.get("場所"), Some("渋谷"));
+}
+
+#[test]
+fn malformed_values() {
+    use keys::ValueError;
+    let (_, header) = parse("{律:軟皇力} {季:夏至} {一位色:}\n").unwrap();
+    /* a rule set that is not known is kept rather than rejected */
+    assert_eq!(header.rule(), Some(keys::Rule::Other(S("軟皇力"))));
+    assert_eq!(
+        header.season(),
+        Err(ValueError {
+            key: "季",
+            value: S("夏至")
+        })
+    );
+    assert!(header.first_color().is_err());
+}
+
+#[test]
+fn start_time() {
+    use keys::DateTime;
+    let start_time = |value: &str| {
+        parse(&format!("{{始時:{value}}}\n"))
+            .unwrap()
+            .1
+            .start_time()
+    };
+    assert_eq!(
+        start_time("2020-02-29t23:59:60.25-03:30"),
+        Ok(Some(DateTime {
+            year: 2020,
+            month: 2,
+            day: 29,
+            hour: 23,
+            minute: 59,
+            second: 60,
+            nanosecond: 250_000_000,
+            offset_minutes: -210
+        }))
+    );
+    for value in &[
+        "2100-02-29T00:00:00Z",
+        "2020-13-01T00:00:00Z",
+        "2020-10-14T24:00:00Z",
+        "2020-10-14T19:39:05",
+        "2020-10-14T19:39:05+0900",
+        "2020-10-14 19:39",
+    ] {
+        assert!(start_time(value).is_err(), "{}", value);
+    }
+}
@@ -0,0 +1,327 @@
+use super::body::{notation1, notation2, notation3, Notation};
+use super::body::{parse_body_elem_with_punctuation, Body, Elem, Punctuation};
+use super::header::Header;
+use crate::error::{self, Expected, IResult, ParseError};
+use nom::combinator::map;
+
+/// A parser that reads a record as it is written, such as one that a live broadcast appends a move at a time to.
+///
+/// Unless it is given by `with_notation`, the notation of the body is detected from its first complete element,
+/// trying the notations in the order `parse_ckka` does. Since notation ① also accepts the form of notation ⓪,
+/// a body detected as ⓪ is read in ① from the first element that only ① can read.
+///
+/// The header is read once the first line of the body begins. Each body element is returned as soon as
+/// a separator follows it; until then, and while it is still a prefix of what might become a valid element,
+/// it is kept as pending rather than being reported as an error.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::Notation;
+/// use cetkaik_kiaak::incremental::IncrementalParser;
+/// use cetkaik_kiaak::parse_ckka;
+/// let mut parser = IncrementalParser::new();
+/// assert_eq!(parser.feed("{律:硬皇力}\n[SY] [J"), Ok(vec![]));
+/// assert_eq!(parser.feed("V]\nXU兵XY無撃"), Ok(vec![]));
+/// assert!(parser.header().is_some());
+/// assert_eq!(parser.pending(), "XU兵XY無撃");
+/// let elems = parser.feed("裁 XAI兵XY無撃裁\n").unwrap();
+/// assert_eq!(elems.len(), 2);
+/// assert_eq!(
+///     parser.finish(),
+///     parse_ckka("{律:硬皇力}\n[SY] [JV]\nXU兵XY無撃裁 XAI兵XY無撃裁\n")
+/// );
+///
+/// let mut parser = IncrementalParser::new();
+/// assert_eq!(parser.feed("[SY] [JV]\nTAI kauk2 T"), Ok(vec![]));
+/// assert_eq!(parser.notation(), None);
+/// assert_eq!(parser.feed("Y. XI kau").unwrap().len(), 1);
+/// assert_eq!(parser.notation(), Some(Notation::Two));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IncrementalParser {
+    /// The complete lines of the header, with their line endings made LF
+    header_text: String,
+
+    /// What has been fed but not yet put into `header_text`, while the body has not begun
+    unread_header: String,
+
+    header: Option<Header>,
+
+    /// Everything fed since the body began
+    body_text: String,
+
+    /// How much of `body_text` has been read into `body`
+    consumed: usize,
+
+    first_line_of_body: usize,
+
+    /// The notation of the body, once it is given or detected
+    notation: Option<Notation>,
+
+    body: Body,
+    error: Option<ParseError>,
+}
+
+impl IncrementalParser {
+    #[must_use]
+    pub fn new() -> Self {
+        IncrementalParser::default()
+    }
+
+    /// A parser for a body written in `notation`, which is then not detected.
+    #[must_use]
+    pub fn with_notation(notation: Notation) -> Self {
+        IncrementalParser {
+            notation: Some(notation),
+            ..IncrementalParser::default()
+        }
+    }
+
+    /// Reads another chunk of the record, returning the body elements completed by it.
+    /// Once an error is returned, every later call returns the same error.
+    pub fn feed(&mut self, chunk: &str) -> Result<Vec<Elem>, ParseError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        let result = self.read(chunk, false);
+        if let Err(error) = &result {
+            self.error = Some(error.clone());
+        }
+        result
+    }
+
+    /// Reads what is still pending as the end of the record, returning the whole record
+    /// as `parse_ckka` would if the body is written in the notation given or detected.
+    pub fn finish(mut self) -> Result<(Header, Body), ParseError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.read("", true)?;
+        let header = match self.header {
+            Some(header) => header,
+            None => super::parse_whole_header(&self.header_text)?,
+        };
+        Ok((header, self.body))
+    }
+
+    /// The header, once the body has begun.
+    #[must_use]
+    pub const fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /// The notation of the body, once it is given or detected from the first complete element.
+    #[must_use]
+    pub const fn notation(&self) -> Option<Notation> {
+        self.notation
+    }
+
+    /// The body elements read so far.
+    #[must_use]
+    pub const fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// What has been fed but is not yet part of a complete element or a complete header.
+    #[must_use]
+    pub fn pending(&self) -> &str {
+        if self.header.is_some() {
+            self.body_text[self.consumed..].trim_start_matches(is_separator)
+        } else {
+            &self.unread_header
+        }
+    }
+
+    fn read(&mut self, chunk: &str, at_end: bool) -> Result<Vec<Elem>, ParseError> {
+        if self.header.is_some() {
+            self.body_text.push_str(chunk);
+        } else {
+            self.unread_header.push_str(chunk);
+            if !self.read_header_lines(at_end)? {
+                return Ok(vec![]);
+            }
+        }
+        self.read_elems(at_end)
+    }
+
+    /// Moves the complete lines of the header into `header_text`, until a line that begins the body is found.
+    /// Returns whether the body has begun, in which case the header is parsed.
+    fn read_header_lines(&mut self, at_end: bool) -> Result<bool, ParseError> {
+        loop {
+            let first_line = self.unread_header.split('\n').next().unwrap_or("");
+            if super::begins_body(first_line) {
+                self.header = Some(super::parse_whole_header(&self.header_text)?);
+                self.first_line_of_body = self.header_text.lines().count() + 1;
+                self.body_text = std::mem::take(&mut self.unread_header);
+                return Ok(true);
+            }
+            let line_end = match self.unread_header.find('\n') {
+                Some(i) => i + 1,
+                None if at_end => self.unread_header.len(),
+                None => return Ok(false),
+            };
+            if line_end == 0 {
+                return Ok(false);
+            }
+            let line: String = self.unread_header.drain(..line_end).collect();
+            self.header_text
+                .push_str(line.trim_end_matches('\n').trim_end_matches('\r'));
+            self.header_text.push('\n');
+        }
+    }
+
+    fn read_elems(&mut self, at_end: bool) -> Result<Vec<Elem>, ParseError> {
+        let mut new = vec![];
+        loop {
+            let rest =
+                self.body_text[self.consumed..].trim_start_matches(is_separator_but_punctuation);
+
+            /* a punctuation mark that comes only after a newline still belongs to the previous element,
+            except in notations ② and ③, where it is a part of the syntax */
+            let keeps_punctuation = matches!(self.notation, Some(Notation::Zero | Notation::One));
+            if let Some(punctuation) = rest.chars().next().and_then(Punctuation::from_char) {
+                if let (true, Some((_, after_last @ None))) =
+                    (keeps_punctuation, self.body.0.last_mut())
+                {
+                    *after_last = Some(punctuation);
+                    let rest = &rest[punctuation.to_char().len_utf8()..];
+                    self.consumed = self.body_text.len() - rest.len();
+                    continue;
+                }
+            }
+
+            if rest.is_empty() {
+                self.consumed = self.body_text.len();
+                return Ok(new);
+            }
+            let (notation, step) = match self.notation {
+                Some(Notation::Zero) => match step(rest, Notation::Zero, at_end) {
+                    /* an element that only notation ① can read makes the rest of the body notation ① */
+                    Step::Failed(e) => match step(rest, Notation::One, at_end) {
+                        Step::Failed(_) => (Notation::Zero, Step::Failed(e)),
+                        read_in_one => (Notation::One, read_in_one),
+                    },
+                    read => (Notation::Zero, read),
+                },
+                Some(notation) => (notation, step(rest, notation, at_end)),
+                None => {
+                    let first_not_failed =
+                        super::NOTATIONS.iter().find_map(|&notation| {
+                            match step(rest, notation, at_end) {
+                                Step::Failed(_) => None,
+                                read => Some((notation, read)),
+                            }
+                        });
+                    let Some(found) = first_not_failed else {
+                        /* as `parse_ckka` does, the error is that of the notation that reads furthest */
+                        let (notation, elem, e) = super::body_failure(rest);
+                        return Err(error::elem_error(
+                            &self.body_text,
+                            elem,
+                            &e,
+                            notation,
+                            self.first_line_of_body,
+                        ));
+                    };
+                    found
+                }
+            };
+            match step {
+                Step::Read(after, elems) => {
+                    self.notation = Some(notation);
+                    self.body.0.extend(elems.iter().cloned());
+                    self.consumed = self.body_text.len() - after.len();
+                    new.extend(elems.into_iter().map(|(elem, _)| elem));
+                }
+                Step::Unfinished => return Ok(new),
+                Step::Failed(e) => {
+                    return Err(error::elem_error(
+                        &self.body_text,
+                        rest,
+                        &e,
+                        notation,
+                        self.first_line_of_body,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// What comes of reading the first element of what is pending.
+enum Step<'a> {
+    /// The elements read, and what follows them
+    Read(&'a str, Vec<(Elem, Option<Punctuation>)>),
+
+    /// The element might still be completed by what is fed later
+    Unfinished,
+
+    Failed(error::Error<&'a str>),
+}
+
+fn step(rest: &str, notation: Notation, at_end: bool) -> Step<'_> {
+    match parse_elems(rest, notation) {
+        Ok((after, elems)) => {
+            let read = &rest[..rest.len() - after.len()];
+            /* in notation ②, spaces may follow the punctuation mark that separates the element */
+            let separated = read.ends_with(|c| separates(c, notation))
+                || read
+                    .trim_end_matches(is_space)
+                    .ends_with(|c| separates(c, notation));
+            if after.is_empty() && !separated && !at_end {
+                Step::Unfinished
+            } else {
+                Step::Read(after, elems)
+            }
+        }
+        Err(e) => {
+            let e = error::error_of(e, rest);
+            if !at_end && may_be_unfinished(&e, notation) {
+                Step::Unfinished
+            } else {
+                Step::Failed(e)
+            }
+        }
+    }
+}
+
+/// Reads a body element written in `notation`. A string literal of notation ③ may stand for two elements.
+fn parse_elems(s: &str, notation: Notation) -> IResult<&str, Vec<(Elem, Option<Punctuation>)>> {
+    match notation {
+        Notation::Zero => map(parse_body_elem_with_punctuation, |elem| vec![elem])(s),
+        Notation::One => map(notation1::parse_body_elem_with_punctuation, |elem| {
+            vec![elem]
+        })(s),
+        Notation::Two => map(notation2::parse_body_elem, |elem| vec![(elem, None)])(s),
+        Notation::Three => map(notation3::parse_body_elems, |elems| {
+            elems.into_iter().map(|elem| (elem, None)).collect()
+        })(s),
+    }
+}
+
+/// Whether the element that fails to parse with `e` might still be completed by what is fed later:
+/// that is, an opened bracket is not closed yet, or nothing has separated the part that failed from the end of the input.
+fn may_be_unfinished(e: &error::Error<&str>, notation: Notation) -> bool {
+    matches!(e.expected, Some(Expected::ClosingBracket(_)))
+        || !e.input.contains(|c| separates(c, notation))
+}
+
+/// Whether `c` separates elements written in `notation`. In notation ②, spaces are a part of an element.
+fn separates(c: char, notation: Notation) -> bool {
+    match notation {
+        Notation::Two => is_separator(c) && !is_space(c),
+        Notation::Zero | Notation::One | Notation::Three => is_separator(c),
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    is_separator_but_punctuation(c) || Punctuation::from_char(c).is_some()
+}
+
+const fn is_separator_but_punctuation(c: char) -> bool {
+    is_space(c) || matches!(c, '\r' | '\n')
+}
+
+const fn is_space(c: char) -> bool {
+    matches!(c, '\t' | ' ' | '\u{00a0}' | '\u{3000}')
+}
@@ -0,0 +1,347 @@
+//! Serialization of the parsed record with serde, enabled by the `serde` feature.
+//!
+//! The shape below is stable, so that a record stored as JSON can be read by a later version of this crate.
+//! It does not follow the layout of the Rust types; every enum is written as an object tagged by `"type"`.
+//!
+//! - A square is a string such as `"XU"`.
+//! - A profession is written by its kanji, such as `"兵"`. Its romanization, such as `"kauk2"`, is also read.
+//! - A colour is `"赤"` or `"黒"`. `"kok1"` and `"huok2"` are also read.
+//! - A hand is written by its kanji, such as `"同色獣"`.
+//! - A season is one of `"spring"`, `"summer"`, `"fall"`, `"winter"`, `"up"` and `"down"`.
+//! - A stick value is a number from 0 to 5; anything else is rejected.
+//! - A value that the record leaves unknown, such as the profession written as `片`, is `null`.
+//!
+//! | Type | Shape |
+//! |------|-------|
+//! | `Header` | `{"info": [header element...], "players": null or [player, player]}` |
+//! | player | `{"player_name": "SY", "point": 20}` |
+//! | header element | `{"type": "value", "value": "..."}`, `{"type": "keyed_value", "key": "律", "value": "硬皇力"}` |
+//! | `Body` | `{"elems": [body element...], "punctuation": [null or "、"...]}` |
+//! | body element | `{"type": "move", "move": move, "comment": null or "..."}` |
+//! | | `{"type": "capture_comment", "color": null or colour, "prof": profession}` |
+//! | | `{"type": "ta_xot_ty_mok", "hand_creation": {"player_name": null or "SY", "hands": null or [hand...], "points": null or 3}, "action": action}` |
+//! | | `{"type": "season_end", "season": season}`, `{"type": "game_end"}`, `{"type": "points", "players": [player, player]}` |
+//! | action | `{"type": "ta_xot", "points": null or 20}`, `{"type": "ty_mok"}` |
+//! | move | `{"type": "no_step_and_no_stick", "src": "XU", "prof": "兵", "dest": "XY"}` and so on |
+//!
+//! A move is tagged by the name of its variant in snake case, and has the fields of the variant under their own names.
+//! The hands are sorted, and `punctuation` has no trailing `null`s, so that the same record is always written the same way.
+//!
+//! Examples:
+//! ```
+//! use cetkaik_kiaak::parse_ckka;
+//! let (header, body) = parse_ckka("{律:硬皇力}\n[SY] [JV]\nXU片XY無撃裁、 手赤兵\n").unwrap();
+//! assert_eq!(
+//!     serde_json::to_string(&header).unwrap(),
+//!     r#"{"info":[{"type":"keyed_value","key":"律","value":"硬皇力"}],"players":[{"player_name":"SY","point":20},{"player_name":"JV","point":20}]}"#
+//! );
+//! assert_eq!(
+//!     serde_json::to_string(&body).unwrap(),
+//!     r#"{"elems":[{"type":"move","move":{"type":"no_step_and_no_stick","src":"XU","prof":null,"dest":"XY"},"comment":null},{"type":"capture_comment","color":"赤","prof":"兵"}],"punctuation":["、"]}"#
+//! );
+//! ```
+
+use super::body::hand::Hand;
+use super::body::movement::{self, Move, PossiblyUnknown};
+use super::body::{notation2, Action, Body, Elem, HandCreation, Punctuation, Season};
+use super::header::{self, PlayerAndPoint};
+use cetkaik_core::{serialize_color, serialize_prof, Color, Profession};
+use nom::branch::alt;
+use nom::combinator::all_consuming;
+use serde::de::{Error, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+
+fn parse_profession(s: &str) -> Option<Profession> {
+    all_consuming(alt((
+        movement::parse_profession,
+        notation2::parse_profession,
+    )))(s)
+    .ok()
+    .map(|(_, prof)| prof)
+}
+
+fn parse_color(s: &str) -> Option<Color> {
+    match s {
+        "赤" | "kok1" => Some(Color::Kok1),
+        "黒" | "huok2" => Some(Color::Huok2),
+        _ => None,
+    }
+}
+
+fn read<'de, D: Deserializer<'de>, T>(
+    s: &str,
+    parse: fn(&str) -> Option<T>,
+    expected: &'static str,
+) -> Result<T, D::Error> {
+    parse(s).ok_or_else(|| D::Error::invalid_value(Unexpected::Str(s), &expected))
+}
+
+const PROFESSION: &str = "a profession such as `兵` or `kauk2`";
+const COLOR: &str = "`赤`, `黒`, `kok1` or `huok2`";
+
+pub(crate) mod profession {
+    use super::{read, serialize_prof, Deserialize, Deserializer, Profession, Serializer};
+
+    /* the signature is the one `serde(with)` asks for */
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(prof: &Profession, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(serialize_prof(*prof))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Profession, D::Error> {
+        read::<D, _>(
+            &String::deserialize(deserializer)?,
+            super::parse_profession,
+            super::PROFESSION,
+        )
+    }
+}
+
+pub(crate) mod possibly_unknown_profession {
+    use super::{
+        read, serialize_prof, Deserialize, Deserializer, PossiblyUnknown, Profession, Serializer,
+    };
+
+    /* the signature is the one `serde(with)` asks for */
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        prof: &PossiblyUnknown<Profession>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match prof {
+            Some(prof) => serializer.serialize_some(serialize_prof(*prof)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PossiblyUnknown<Profession>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| read::<D, _>(&s, super::parse_profession, super::PROFESSION))
+            .transpose()
+    }
+}
+
+pub(crate) mod possibly_unknown_color {
+    use super::{
+        read, serialize_color, Color, Deserialize, Deserializer, PossiblyUnknown, Serializer,
+    };
+
+    /* the signature is the one `serde(with)` asks for */
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        color: &PossiblyUnknown<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => serializer.serialize_some(serialize_color(*color)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PossiblyUnknown<Color>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| read::<D, _>(&s, super::parse_color, super::COLOR))
+            .transpose()
+    }
+}
+
+/// The value of a water stick or a bridge stick, or `null` if the record leaves it unknown.
+/// A value outside 0 to 5 is rejected, since no stick can show it and it could not be written back as CKKA.
+pub(crate) mod stick_size {
+    use super::{
+        Deserialize, Deserializer, Error, PossiblyUnknown, Serialize, Serializer, Unexpected,
+    };
+
+    /* the signature is the one `serde(with)` asks for */
+    #[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(
+        size: &PossiblyUnknown<i32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        size.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PossiblyUnknown<i32>, D::Error> {
+        match Option::<i32>::deserialize(deserializer)? {
+            Some(n) if !(0..=5).contains(&n) => Err(D::Error::invalid_value(
+                Unexpected::Signed(n.into()),
+                &"a stick value from 0 to 5",
+            )),
+            size => Ok(size),
+        }
+    }
+}
+
+/// The hands of a hand creation, written as a sorted array, or `null` if the record leaves them unknown.
+pub(crate) mod hands {
+    use super::{Deserialize, Deserializer, Hand, HashSet, PossiblyUnknown, Serialize, Serializer};
+
+    /* the signature is the one `serde(with)` asks for */
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S: Serializer>(
+        hands: &PossiblyUnknown<HashSet<Hand>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let hands: Option<Vec<_>> = hands.as_ref().map(|hands| {
+            let mut hands: Vec<_> = hands.iter().collect();
+            hands.sort();
+            hands
+        });
+        hands.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PossiblyUnknown<HashSet<Hand>>, D::Error> {
+        Ok(
+            Option::<Vec<Hand>>::deserialize(deserializer)?
+                .map(|hands| hands.into_iter().collect()),
+        )
+    }
+}
+
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.kanji())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        read::<D, _>(
+            &String::deserialize(deserializer)?,
+            Hand::from_kanji,
+            "a hand such as `同色獣`",
+        )
+    }
+}
+
+impl Serialize for Punctuation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.to_char())
+    }
+}
+
+impl<'de> Deserialize<'de> for Punctuation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let c = char::deserialize(deserializer)?;
+        Punctuation::from_char(c)
+            .ok_or_else(|| D::Error::invalid_value(Unexpected::Char(c), &"a punctuation mark"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct BodyRepr {
+    elems: Vec<Elem>,
+    #[serde(default)]
+    punctuation: Vec<Option<Punctuation>>,
+}
+
+impl From<Body> for BodyRepr {
+    fn from(Body(pairs): Body) -> Self {
+        let (elems, mut punctuation): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        while punctuation.last() == Some(&None) {
+            punctuation.pop();
+        }
+        BodyRepr { elems, punctuation }
+    }
+}
+
+impl From<BodyRepr> for Body {
+    /// A body whose `punctuation` is shorter than `elems` has no punctuation marks after the rest of the elements.
+    fn from(BodyRepr { elems, punctuation }: BodyRepr) -> Self {
+        let punctuation = punctuation.into_iter().chain(std::iter::repeat(None));
+        Body(elems.into_iter().zip(punctuation).collect())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ElemRepr {
+    Move {
+        #[serde(rename = "move")]
+        movement: Move,
+        comment: Option<String>,
+    },
+    CaptureComment {
+        #[serde(with = "possibly_unknown_color")]
+        color: PossiblyUnknown<Color>,
+        #[serde(with = "profession")]
+        prof: Profession,
+    },
+    TaXotTyMok {
+        hand_creation: HandCreation,
+        action: Action,
+    },
+    SeasonEnd {
+        season: Season,
+    },
+    GameEnd,
+    Points {
+        players: (PlayerAndPoint, PlayerAndPoint),
+    },
+}
+
+impl From<Elem> for ElemRepr {
+    fn from(elem: Elem) -> Self {
+        match elem {
+            Elem::Move(movement, comment) => ElemRepr::Move { movement, comment },
+            Elem::CaptureComment(color, prof) => ElemRepr::CaptureComment { color, prof },
+            Elem::TaXotTyMok(hand_creation, action) => ElemRepr::TaXotTyMok {
+                hand_creation,
+                action,
+            },
+            Elem::SeasonEnd(season) => ElemRepr::SeasonEnd { season },
+            Elem::GameEnd => ElemRepr::GameEnd,
+            Elem::Points(p1, p2) => ElemRepr::Points { players: (p1, p2) },
+        }
+    }
+}
+
+impl From<ElemRepr> for Elem {
+    fn from(elem: ElemRepr) -> Self {
+        match elem {
+            ElemRepr::Move { movement, comment } => Elem::Move(movement, comment),
+            ElemRepr::CaptureComment { color, prof } => Elem::CaptureComment(color, prof),
+            ElemRepr::TaXotTyMok {
+                hand_creation,
+                action,
+            } => Elem::TaXotTyMok(hand_creation, action),
+            ElemRepr::SeasonEnd { season } => Elem::SeasonEnd(season),
+            ElemRepr::GameEnd => Elem::GameEnd,
+            ElemRepr::Points { players: (p1, p2) } => Elem::Points(p1, p2),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum HeaderElemRepr {
+    Value { value: String },
+    KeyedValue { key: String, value: String },
+}
+
+impl From<header::Elem> for HeaderElemRepr {
+    fn from(elem: header::Elem) -> Self {
+        match elem {
+            header::Elem::Value(value) => HeaderElemRepr::Value { value },
+            header::Elem::KeyedValue(key, value) => HeaderElemRepr::KeyedValue { key, value },
+        }
+    }
+}
+
+impl From<HeaderElemRepr> for header::Elem {
+    fn from(elem: HeaderElemRepr) -> Self {
+        match elem {
+            HeaderElemRepr::Value { value } => header::Elem::Value(value),
+            HeaderElemRepr::KeyedValue { key, value } => header::Elem::KeyedValue(key, value),
+        }
+    }
+}
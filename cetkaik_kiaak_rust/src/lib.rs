@@ -12,25 +12,56 @@ extern crate regex;
 
 use regex::Regex;
 
-use body::{parse_body_elem, Body};
+use body::{parse_body_elem_with_punctuation, Body, Notation};
 
 type CKKA = (header::Header, Body);
 
 pub mod body;
+pub mod capture;
+pub mod consistency;
+pub mod declaration;
+pub mod error;
+pub mod incremental;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod relative;
+pub mod replay;
+pub mod rule;
+pub mod score;
+pub mod season;
+pub mod serialize;
+pub mod turn;
 
+use crate::error::Error;
+use crate::error::IResult;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::bytes::complete::take_until;
 use nom::character::complete::char;
 use nom::character::complete::one_of;
-use nom::combinator::opt;
-use nom::error::{Error, ErrorKind};
+use nom::combinator::{map, opt};
+use nom::error::ErrorKind;
 use nom::multi::many0;
 use nom::multi::many1;
 use nom::Err;
-use nom::IResult;
 
 fn parse_braced_string(s: &str, open: char, close: char) -> IResult<&str, &str> {
+    let (no_used, in_string) = parse_braced_string_without_trailing_spaces(s, open, close)?;
+    let (no_used, ()) = skip_spaces_and_newlines(no_used)?;
+
+    if in_string.contains('\n') || in_string.contains('\r') {
+        return Err(Err::Error(Error::new(no_used, ErrorKind::Verify)));
+        /* neither key nor value in the header can contain a newline */
+    }
+
+    Ok((no_used, in_string))
+}
+
+fn parse_braced_string_without_trailing_spaces(
+    s: &str,
+    open: char,
+    close: char,
+) -> IResult<&str, &str> {
     let (no_used, vec) = many0(char('#'))(s)?;
     let (no_used, _) = char(open)(no_used)?;
 
@@ -40,27 +71,30 @@ fn parse_braced_string(s: &str, open: char, close: char) -> IResult<&str, &str>
         close,
         (0..vec.len()).map(|_| "#").collect::<String>()
     );
-    let (no_used, in_string) = take_until(&*end_pattern)(no_used)?;
-    let (no_used, _) = tag(&*end_pattern)(no_used)?;
-
-    let (no_used, _) = skip_spaces_and_newlines(no_used)?;
-
-    if in_string.contains('\n') || in_string.contains('\r') {
-        return Err(Err::Error(Error::new(no_used, ErrorKind::Verify)));
-        /* neither key nor value in the header can contain a newline */
-    }
+    let until_closed = |no_used| -> IResult<&str, &str> {
+        let (no_used, in_string) = take_until(&*end_pattern)(no_used)?;
+        let (no_used, _) = tag(&*end_pattern)(no_used)?;
+        Ok((no_used, in_string))
+    };
+    /* a bracket that is not closed is reported where it is opened */
+    let (no_used, in_string) = until_closed(no_used).map_err(|e| {
+        e.map(|e: Error<&str>| e.expecting(s, error::Expected::ClosingBracket(close)))
+    })?;
 
     Ok((no_used, in_string))
 }
 
 pub fn parse_numeral(s: &str) -> IResult<&str, i64> {
-    alt((parse_arabic_numeral, parse_pekzep_numeral))(s)
+    error::expect(
+        error::Expected::Numeral,
+        alt((parse_arabic_numeral, parse_pekzep_numeral)),
+    )(s)
 }
 
 pub fn parse_arabic_numeral(s: &str) -> IResult<&str, i64> {
     let (rest, neg) = opt(char('-'))(s)?;
     let (rest, num_vec) = many1(one_of("0123456789"))(rest)?;
-    let acc = i64::from_str_radix(&num_vec.into_iter().collect::<String>(), 10);
+    let acc = num_vec.into_iter().collect::<String>().parse::<i64>();
     match acc {
         Ok(num) => Ok((rest, if neg.is_some() { -num } else { num })),
         Err(_) => Err(Err::Error(Error::new(rest, ErrorKind::Verify))), /* unparsable arabic numeral */
@@ -79,51 +113,149 @@ pub fn parse_header(input: &str) -> IResult<&str, header::Header> {
     header::parse(input)
 }
 
-pub fn parse_ckka(s: &str) -> Result<CKKA, String> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r#"^\s*[KLNTZXCMP"]"#).unwrap();
+/// Parses a CKKA file given as bytes. The file must be in UTF-8 without a byte order mark.
+/// Lines may end in either CRLF or LF.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::{parse_ckka, parse_ckka_bytes};
+/// use cetkaik_kiaak::error::{ParseError, Position};
+/// let text = "{律:硬皇力}\r\n[SY] [JV]\r\nXU兵XY無撃裁\r\n";
+/// assert_eq!(parse_ckka_bytes(text.as_bytes()), parse_ckka(text));
+/// assert_eq!(
+///     parse_ckka_bytes(b"\xEF\xBB\xBFXU\xE5\x85\xB5XY"),
+///     Err(ParseError::ByteOrderMark)
+/// );
+/// assert_eq!(
+///     parse_ckka_bytes(b"[SY] [JV]\nXU\xE5\x85XY"),
+///     Err(ParseError::InvalidUtf8 { position: Position { line: 2, column: 3 } })
+/// );
+/// ```
+pub fn parse_ckka_bytes(bytes: &[u8]) -> Result<CKKA, error::ParseError> {
+    /* a UTF-16 file is reported by its byte order mark rather than as invalid UTF-8 */
+    const BYTE_ORDER_MARKS: [&[u8]; 3] = [b"\xEF\xBB\xBF", b"\xFE\xFF", b"\xFF\xFE"];
+    if BYTE_ORDER_MARKS.iter().any(|bom| bytes.starts_with(bom)) {
+        return Err(error::ParseError::ByteOrderMark);
+    }
+    let s = std::str::from_utf8(bytes).map_err(|e| {
+        /* no replacement character is produced, since the bytes up to `valid_up_to` are valid */
+        let valid = String::from_utf8_lossy(&bytes[..e.valid_up_to()]);
+        error::ParseError::InvalidUtf8 {
+            position: error::position_of(&valid, &valid[valid.len()..], 1),
+        }
+    })?;
+    parse_ckka(s)
+}
+
+/// Parses a CKKA file. Lines may end in either CRLF or LF, and a byte order mark is rejected.
+pub fn parse_ckka(s: &str) -> Result<CKKA, error::ParseError> {
+    if s.starts_with('\u{feff}') {
+        return Err(error::ParseError::ByteOrderMark);
     }
 
+    let (header, body) = split_header_and_body(s);
+    let parsed_head = parse_whole_header(&header)?;
+
+    let first_line_of_body = header.lines().count() + 1;
+    let Ok(("", parsed_body)) = parse_body(&body) else {
+        return Err(error::body_error(&body, first_line_of_body));
+    };
+
+    Ok((parsed_head, parsed_body))
+}
+
+/// Splits the text into the lines of the header and those of the body, with their line endings made LF.
+fn split_header_and_body(s: &str) -> (String, String) {
     let mut header = String::new();
     let mut body = String::new();
     let mut is_body = false;
     for l in s.lines() {
-        if RE.is_match(l) {
+        if begins_body(l) {
             is_body = true;
         }
 
         if is_body {
-            body.push_str(&l);
+            body.push_str(l);
             body.push('\n');
         } else {
-            header.push_str(&l);
+            header.push_str(l);
             header.push('\n');
         }
     }
+    (header, body)
+}
 
-    let parsed_head = match header::parse(&header) {
-        Ok(("", parsed_head)) => parsed_head,
-        Ok((a, _)) => {
-            return Err(format!(
-                "Unparsable fragment `{}` left while parsing header",
-                a
-            ))
-        }
-        Err(e) => return Err(format!("Failed to parse header, with error `{:?}`", e)),
-    };
+/// Where each element of the body begins in a CKKA file that `parse_ckka` reads without an error,
+/// so that an issue found in `Body.0[i]` can be reported at a line and a column.
+/// Only a body written in notation ⓪ or ① is supported; `None` is returned for any other body.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body_elem_positions;
+/// use cetkaik_kiaak::error::Position;
+/// assert_eq!(
+///     body_elem_positions("{律:硬皇力}\n[SY] [JV]\nXU兵XY無撃裁 XAI兵XY無撃裁\n  XU兵XY 手兵\n"),
+///     Some(vec![
+///         Position { line: 3, column: 1 },
+///         Position { line: 3, column: 10 },
+///         Position { line: 4, column: 3 },
+///         Position { line: 4, column: 9 },
+///     ])
+/// );
+/// assert_eq!(body_elem_positions("[SY] [JV]\nXU kauk2 XY.\n"), None);
+/// ```
+#[must_use]
+pub fn body_elem_positions(s: &str) -> Option<Vec<error::Position>> {
+    type ElemParser =
+        for<'a> fn(&'a str) -> IResult<&'a str, (body::Elem, Option<body::Punctuation>)>;
+    const PARSERS: [ElemParser; 2] = [
+        parse_body_elem_with_punctuation,
+        body::notation1::parse_body_elem_with_punctuation,
+    ];
 
-    let parsed_body = match parse_body(&body) {
-        Ok(("", parsed_body)) => parsed_body,
-        Ok((a, _)) => {
-            return Err(format!(
-                "Unparsable fragment `{}` left while parsing body",
-                a
-            ))
+    let (header, body) = split_header_and_body(s);
+    let first_line_of_body = header.lines().count() + 1;
+    PARSERS.iter().find_map(|parse_elem| {
+        let mut positions = vec![];
+        let (mut rest, ()) = skip_spaces_and_newlines(&body).ok()?;
+        while let Ok((after, _)) = parse_elem(rest) {
+            positions.push(error::position_of(&body, rest, first_line_of_body));
+            rest = after;
         }
-        Err(e) => return Err(format!("Failed to parse header, with error `{:?}`", e)),
-    };
+        if rest.is_empty() {
+            Some(positions)
+        } else {
+            None
+        }
+    })
+}
 
-    Ok((parsed_head, parsed_body))
+/// Whether the line is the first line of the body, which begins with a square or, in notation ③, with `"`.
+#[allow(clippy::non_std_lazy_statics)]
+pub(crate) fn begins_body(line: &str) -> bool {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r#"^\s*[KLNTZXCMP"]"#).unwrap();
+    }
+    RE.is_match(line)
+}
+
+/// Parses the lines of the header, which must be read to their end.
+pub(crate) fn parse_whole_header(header: &str) -> Result<header::Header, error::ParseError> {
+    match header::parse(header) {
+        Ok(("", parsed_head)) => Ok(parsed_head),
+        Ok((rest, _)) => Err(error::ParseError::Header {
+            position: error::position_of(header, rest, 1),
+            expected: error::diagnose_header(rest, false),
+        }),
+        /* `header::parse` only fails when it finds just one player */
+        Err(e) => {
+            let rest = error::error_of(e, header).input;
+            Err(error::ParseError::Header {
+                position: error::position_of(header, rest, 1),
+                expected: error::diagnose_header(rest, true),
+            })
+        }
+    }
 }
 
 fn skip_spaces_and_newlines(s: &str) -> IResult<&str, ()> {
@@ -131,11 +263,84 @@ fn skip_spaces_and_newlines(s: &str) -> IResult<&str, ()> {
     Ok((no_used, ()))
 }
 
+/// Parses the body, detecting which notation it is written in.
+/// Notation ⓪ is tried first; if it does not cover the whole input, notations ①, ② and ③ are tried in this order.
+/// If none of them does, the error is that of the notation that reads furthest.
 pub fn parse_body(s: &str) -> IResult<&str, Body> {
-    let (rest, _) = skip_spaces_and_newlines(s)?;
-    let (rest, vec) = many0(parse_body_elem)(rest)?;
+    let (rest, (_, body)) = parse_body_and_detect_notation(s)?;
+    Ok((rest, body))
+}
+
+/// The notations in the order in which they are tried.
+const NOTATIONS: [Notation; 4] = [
+    Notation::Zero,
+    Notation::One,
+    Notation::Two,
+    Notation::Three,
+];
+
+pub fn parse_body_and_detect_notation(s: &str) -> IResult<&str, (Notation, Body)> {
+    for notation in &NOTATIONS {
+        if let Ok(("", body)) = parse_body_with_notation(s, *notation) {
+            return Ok(("", (*notation, body)));
+        }
+    }
+
+    Err(Err::Error(body_failure(s).2))
+}
 
-    Ok((rest, Body(vec)))
+/// Where the body stops being readable, in the notation that reads furthest:
+/// the notation, the element that cannot be parsed, and the error of its parser.
+/// Notation ⓪ is taken if several notations read equally far.
+pub(crate) fn body_failure(s: &str) -> (Notation, &str, Error<&str>) {
+    NOTATIONS
+        .iter()
+        .map(|&notation| {
+            let (elem, error) = body_failure_with_notation(s, notation);
+            (notation, elem, error)
+        })
+        .min_by_key(|(_, _, error)| error.remaining())
+        .expect("there is at least one notation")
+}
+
+/// Reads the body element by element as `parse_body_with_notation` does,
+/// returning the element that cannot be parsed and the error of its parser.
+pub(crate) fn body_failure_with_notation(s: &str, notation: Notation) -> (&str, Error<&str>) {
+    let mut rest = s.trim_start_matches(&['\t', '\r', '\n', ' ', '\u{00a0}', '\u{3000}'][..]);
+    loop {
+        let read = match notation {
+            Notation::Zero => parse_body_elem_with_punctuation(rest).map(|(after, _)| after),
+            Notation::One => {
+                body::notation1::parse_body_elem_with_punctuation(rest).map(|(after, _)| after)
+            }
+            Notation::Two => body::notation2::parse_body_elem(rest).map(|(after, _)| after),
+            Notation::Three => body::notation3::parse_body_elems(rest).map(|(after, _)| after),
+        };
+        match read {
+            Ok(after) if after.len() < rest.len() => rest = after,
+            /* `many0` stops at an element that reads nothing */
+            Ok(_) => return (rest, Error::new(rest, ErrorKind::Many0)),
+            Err(e) => return (rest, error::error_of(e, rest)),
+        }
+    }
+}
+
+pub fn parse_body_with_notation(s: &str, notation: Notation) -> IResult<&str, Body> {
+    let (rest, ()) = skip_spaces_and_newlines(s)?;
+    /* only notations ⓪ and ① keep the punctuation; ② and ③ use it as a part of their syntax */
+    match notation {
+        Notation::Zero => map(many0(parse_body_elem_with_punctuation), Body)(rest),
+        Notation::One => map(
+            many0(body::notation1::parse_body_elem_with_punctuation),
+            Body,
+        )(rest),
+        Notation::Two => map(many0(body::notation2::parse_body_elem), Body::from)(rest),
+        Notation::Three => map(many0(body::notation3::parse_body_elems), |vecs| {
+            Body::from(vecs.into_iter().flatten().collect::<Vec<_>>())
+        })(rest),
+    }
 }
 #[cfg(test)]
+/* the test that came with the crate is kept as it was written */
+#[allow(clippy::needless_raw_string_hashes)]
 mod tests;
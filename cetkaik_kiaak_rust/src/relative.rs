@@ -0,0 +1,360 @@
+use super::body::movement::{self, PossiblyUnknown};
+use super::body::{Action, Body, Elem};
+use super::replay::{replay, ReplayError, State};
+use cetkaik_core::absolute::Side;
+use cetkaik_core::perspective::{to_absolute_coord, to_relative_coord, Perspective};
+use cetkaik_core::{relative, Color, Profession};
+
+/// A move in the coordinates of `cetkaik_core::relative`, which depend on the perspective.
+/// The variants and fields are those of [`movement::Move`], except that every square is a `relative::Coord`.
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub enum Move {
+    NoStepAndNoStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        dest: relative::Coord,
+    },
+
+    NoStepAndWaterStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        dest: relative::Coord,
+        water_stick_size: PossiblyUnknown<i32>,
+        water_stick_successful: bool,
+    },
+
+    StepAndNoStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        step: relative::Coord,
+        dest: relative::Coord,
+    },
+
+    StepAndWaterStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        step: relative::Coord,
+        dest: relative::Coord,
+        water_stick_size: PossiblyUnknown<i32>,
+        water_stick_successful: bool,
+    },
+
+    StepAndBridgeStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        step: relative::Coord,
+        dest: relative::Coord,
+        bridge_stick_size: PossiblyUnknown<i32>,
+        bridge_stick_successful: bool,
+    },
+
+    StepAndBridgeStickAndWaterStick {
+        src: relative::Coord,
+        prof: PossiblyUnknown<Profession>,
+        step: relative::Coord,
+        dest: relative::Coord,
+        bridge_stick_size: PossiblyUnknown<i32>,
+        water_stick_size: PossiblyUnknown<i32>,
+        water_stick_successful: bool,
+    },
+
+    TamNoStep {
+        src: relative::Coord,
+        first_dest: PossiblyUnknown<relative::Coord>,
+        second_dest: relative::Coord,
+    },
+
+    TamStepUnspecified {
+        src: relative::Coord,
+        step: relative::Coord,
+        second_dest: relative::Coord,
+    },
+
+    TamStepDuringFormer {
+        src: relative::Coord,
+        step: relative::Coord,
+        first_dest: PossiblyUnknown<relative::Coord>,
+        second_dest: relative::Coord,
+    },
+
+    TamStepDuringLatter {
+        src: relative::Coord,
+        first_dest: PossiblyUnknown<relative::Coord>,
+        step: relative::Coord,
+        second_dest: relative::Coord,
+    },
+
+    Parachute {
+        color: PossiblyUnknown<Color>,
+        prof: Profession,
+        dest: relative::Coord,
+    },
+}
+
+/// Rebuilds the move of one enum as the same variant of the other, converting every square with `$f`.
+macro_rules! map_squares {
+    ($m:expr, $from:path, $to:path, $f:expr) => {{
+        use $from as From;
+        use $to as To;
+        let f = $f;
+        match *$m {
+            From::NoStepAndNoStick { src, prof, dest } => To::NoStepAndNoStick {
+                src: f(src),
+                prof,
+                dest: f(dest),
+            },
+            From::NoStepAndWaterStick {
+                src,
+                prof,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            } => To::NoStepAndWaterStick {
+                src: f(src),
+                prof,
+                dest: f(dest),
+                water_stick_size,
+                water_stick_successful,
+            },
+            From::StepAndNoStick {
+                src,
+                prof,
+                step,
+                dest,
+            } => To::StepAndNoStick {
+                src: f(src),
+                prof,
+                step: f(step),
+                dest: f(dest),
+            },
+            From::StepAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                water_stick_size,
+                water_stick_successful,
+            } => To::StepAndWaterStick {
+                src: f(src),
+                prof,
+                step: f(step),
+                dest: f(dest),
+                water_stick_size,
+                water_stick_successful,
+            },
+            From::StepAndBridgeStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_size,
+                bridge_stick_successful,
+            } => To::StepAndBridgeStick {
+                src: f(src),
+                prof,
+                step: f(step),
+                dest: f(dest),
+                bridge_stick_size,
+                bridge_stick_successful,
+            },
+            From::StepAndBridgeStickAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_size,
+                water_stick_size,
+                water_stick_successful,
+            } => To::StepAndBridgeStickAndWaterStick {
+                src: f(src),
+                prof,
+                step: f(step),
+                dest: f(dest),
+                bridge_stick_size,
+                water_stick_size,
+                water_stick_successful,
+            },
+            From::TamNoStep {
+                src,
+                first_dest,
+                second_dest,
+            } => To::TamNoStep {
+                src: f(src),
+                first_dest: first_dest.map(f),
+                second_dest: f(second_dest),
+            },
+            From::TamStepUnspecified {
+                src,
+                step,
+                second_dest,
+            } => To::TamStepUnspecified {
+                src: f(src),
+                step: f(step),
+                second_dest: f(second_dest),
+            },
+            From::TamStepDuringFormer {
+                src,
+                step,
+                first_dest,
+                second_dest,
+            } => To::TamStepDuringFormer {
+                src: f(src),
+                step: f(step),
+                first_dest: first_dest.map(f),
+                second_dest: f(second_dest),
+            },
+            From::TamStepDuringLatter {
+                src,
+                first_dest,
+                step,
+                second_dest,
+            } => To::TamStepDuringLatter {
+                src: f(src),
+                first_dest: first_dest.map(f),
+                step: f(step),
+                second_dest: f(second_dest),
+            },
+            From::Parachute { color, prof, dest } => To::Parachute {
+                color,
+                prof,
+                dest: f(dest),
+            },
+        }
+    }};
+}
+
+/// The perspective of the player who makes the move, in which their pieces point upward.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::relative::perspective_of;
+/// use cetkaik_core::absolute::Side;
+/// use cetkaik_core::perspective::Perspective;
+/// assert_eq!(perspective_of(Side::IASide), Perspective::IaIsDownAndPointsUpward);
+/// assert_eq!(perspective_of(Side::ASide), Perspective::IaIsUpAndPointsDownward);
+/// ```
+#[must_use]
+pub const fn perspective_of(mover: Side) -> Perspective {
+    match mover {
+        Side::IASide => Perspective::IaIsDownAndPointsUpward,
+        Side::ASide => Perspective::IaIsUpAndPointsDownward,
+    }
+}
+
+/// Converts the move into the coordinates seen from the perspective.
+/// Seen from the mover, with [`perspective_of`], the row `0` is the farthest from the mover.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::movement::parse;
+/// use cetkaik_kiaak::relative::{perspective_of, to_relative_move, Move};
+/// use cetkaik_core::absolute::Side;
+/// use cetkaik_core::Profession;
+/// let m = parse("XAI兵XY無撃裁").unwrap().1;
+/// assert_eq!(
+///     to_relative_move(&m, perspective_of(Side::IASide)),
+///     Move::NoStepAndNoStick { src: [6, 5], prof: Some(Profession::Kauk2), dest: [5, 5] }
+/// );
+/// let m = parse("XI兵XU無撃裁").unwrap().1;
+/// assert_eq!(
+///     to_relative_move(&m, perspective_of(Side::ASide)),
+///     Move::NoStepAndNoStick { src: [6, 3], prof: Some(Profession::Kauk2), dest: [5, 3] }
+/// );
+/// ```
+#[must_use]
+pub fn to_relative_move(m: &movement::Move, p: Perspective) -> Move {
+    map_squares!(m, movement::Move, Move, |coord| to_relative_coord(coord, p))
+}
+
+/// Converts the move seen from the perspective back into absolute coordinates.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::movement::parse;
+/// use cetkaik_kiaak::relative::{perspective_of, to_absolute_move, to_relative_move};
+/// use cetkaik_core::absolute::Side;
+/// let m = parse("PAU皇[MAU]CAIMAU").unwrap().1;
+/// for side in &[Side::ASide, Side::IASide] {
+///     let p = perspective_of(*side);
+///     assert_eq!(to_absolute_move(&to_relative_move(&m, p), p), m);
+/// }
+/// ```
+#[must_use]
+pub fn to_absolute_move(m: &Move, p: Perspective) -> movement::Move {
+    map_squares!(m, Move, movement::Move, |coord| to_absolute_coord(coord, p))
+}
+
+/// A move of the body seen from the player who makes it.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct RelativeMove {
+    /// The index of the move in `Body.0`
+    pub index: usize,
+    pub mover: Side,
+
+    /// The move in the coordinates of [`perspective_of`] the mover
+    pub movement: Move,
+}
+
+/// Replays the body and gives each move seen from the player who makes it.
+/// A move of Tam2 that opens a season belongs to neither side, so its mover is found out from the move that follows;
+/// a move whose mover cannot be found out in this way is left out.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::relative::{relative_moves, Move};
+/// use cetkaik_core::absolute::Side;
+/// let (_, body) = parse_ckka("[SY] [JV]\nZO皇[ZU]TO XI兵XU無撃裁\n").unwrap();
+/// let moves = relative_moves(&body).unwrap();
+/// assert_eq!(moves.len(), 2);
+/// assert_eq!((moves[0].index, moves[0].mover), (0, Side::IASide));
+/// assert_eq!((moves[1].index, moves[1].mover), (1, Side::ASide));
+/// assert!(matches!(moves[1].movement, Move::NoStepAndNoStick { src: [6, 3], .. }));
+/// ```
+pub fn relative_moves(body: &Body) -> Result<Vec<RelativeMove>, ReplayError> {
+    Ok(movers(body)?
+        .into_iter()
+        .zip(body.elems())
+        .enumerate()
+        .filter_map(|(index, (mover, elem))| match (mover, elem) {
+            (Some(mover), Elem::Move(m, _)) => Some(RelativeMove {
+                index,
+                mover,
+                movement: to_relative_move(m, perspective_of(mover)),
+            }),
+            _ => None,
+        })
+        .collect())
+}
+
+/// The side that makes each move of the body, indexed in the same way as `Body.0`. Elements other than moves are `None`.
+pub(crate) fn movers(body: &Body) -> Result<Vec<Option<Side>>, ReplayError> {
+    let mut movers = Vec::with_capacity(body.0.len());
+    let mut before = State::initial();
+    for (elem, after) in body.elems().zip(replay(body)) {
+        let after = after?;
+        movers.push(match elem {
+            Elem::Move(..) => before
+                .side_to_move
+                .or_else(|| after.side_to_move.map(|side| !side)),
+            _ => None,
+        });
+        before = after;
+    }
+
+    /* the turn passes with every move, and a declaration of ta xot starts a season in which it is not yet known */
+    let mut next = None;
+    for (mover, elem) in movers.iter_mut().zip(body.elems()).rev() {
+        match elem {
+            Elem::Move(..) => {
+                if mover.is_none() {
+                    *mover = next.map(|side: Side| !side);
+                }
+                next = *mover;
+            }
+            Elem::TaXotTyMok(_, Action::TaXot(_)) => next = None,
+            _ => {}
+        }
+    }
+    Ok(movers)
+}
@@ -0,0 +1,774 @@
+use super::body::movement::{Move, PossiblyUnknown};
+use super::body::{Action, Body, Elem, Punctuation};
+use super::rule::Config;
+use cetkaik_core::absolute::{
+    distance, serialize_coord, yhuap_initial_board, Column, Coord, Field, NonTam2Piece, Piece, Row,
+    Side,
+};
+use cetkaik_core::{serialize_color, serialize_prof, Color, Profession};
+use std::fmt;
+
+const ALL_SQUARES: [Coord; 81] = {
+    const ROWS: [Row; 9] = [
+        Row::A,
+        Row::E,
+        Row::I,
+        Row::U,
+        Row::O,
+        Row::Y,
+        Row::AI,
+        Row::AU,
+        Row::IA,
+    ];
+    const COLUMNS: [Column; 9] = [
+        Column::K,
+        Column::L,
+        Column::N,
+        Column::T,
+        Column::Z,
+        Column::X,
+        Column::C,
+        Column::M,
+        Column::P,
+    ];
+    let mut squares = [Coord(Row::A, Column::K); 81];
+    let mut i = 0;
+    while i < 81 {
+        squares[i] = Coord(ROWS[i / 9], COLUMNS[i % 9]);
+        i += 1;
+    }
+    squares
+};
+
+/// The position of the game: the board, both hands, and whose turn it is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct State {
+    pub field: Field,
+
+    /// The side that makes the next move. This is unknown at the beginning of a season,
+    /// and is found out from the first move that moves a piece belonging to either side.
+    pub side_to_move: Option<Side>,
+
+    /// Whether the last move was made by Tam2
+    pub tam_moved_last: bool,
+}
+
+/// Why a move cannot be applied to the position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReplayErrorKind {
+    /// There is no piece on the source square
+    NoPieceAt(Coord),
+
+    /// A move written with a profession starts from the square of Tam2
+    UnexpectedTam(Coord),
+
+    /// A move written with `皇` starts from a square that does not hold Tam2
+    NotTam(Coord),
+
+    /// The profession written in the record is not that of the piece on the source square
+    ProfessionMismatch {
+        square: Coord,
+        written: Profession,
+        actual: Profession,
+    },
+
+    /// The piece on the source square belongs to the side that does not have the move
+    WrongSide(Coord),
+
+    /// There is no piece to step on
+    NothingToStepOn(Coord),
+
+    /// Tam2 can never be captured
+    CannotCaptureTam(Coord),
+
+    /// A piece cannot capture one of its own side
+    CannotCaptureOwnPiece(Coord),
+
+    /// Tam2 or a parachuted piece cannot land on an occupied square
+    Occupied(Coord),
+
+    /// The piece to be parachuted is not in the hand of the side to move
+    NotInHand {
+        color: PossiblyUnknown<Color>,
+        prof: Profession,
+    },
+
+    /// The colour of the piece to be parachuted is written as `或`, but the hand has the profession in both colours
+    AmbiguousColor(Profession),
+
+    /// The squares Tam2 goes through are left out, and more than one way fits between the squares written
+    AmbiguousTamPath(Coord),
+
+    /// The squares Tam2 goes through are left out, and no way fits between the squares written
+    NoTamPath(Coord),
+
+    /// Tam2 is moved right after it was moved, which the rules in use forbid
+    TamMovedTwiceInARow(Coord),
+}
+
+/// A move that could not be replayed, together with the index of the element in `Body.0`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ReplayError {
+    pub index: usize,
+    pub kind: ReplayErrorKind,
+}
+
+impl fmt::Display for ReplayErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ReplayErrorKind::NoPieceAt(square) => {
+                write!(f, "there is no piece on {}", serialize_coord(square))
+            }
+            ReplayErrorKind::UnexpectedTam(square) => write!(
+                f,
+                "{} holds Tam2, but the move is written with a profession",
+                serialize_coord(square)
+            ),
+            ReplayErrorKind::NotTam(square) => write!(
+                f,
+                "the move is written with `皇`, but {} does not hold Tam2",
+                serialize_coord(square)
+            ),
+            ReplayErrorKind::ProfessionMismatch {
+                square,
+                written,
+                actual,
+            } => write!(
+                f,
+                "the piece on {} is {}, not {}",
+                serialize_coord(square),
+                serialize_prof(actual),
+                serialize_prof(written)
+            ),
+            ReplayErrorKind::WrongSide(square) => write!(
+                f,
+                "the piece on {} belongs to the side that does not have the move",
+                serialize_coord(square)
+            ),
+            ReplayErrorKind::NothingToStepOn(square) => {
+                write!(
+                    f,
+                    "there is no piece on {} to step on",
+                    serialize_coord(square)
+                )
+            }
+            ReplayErrorKind::CannotCaptureTam(square) => {
+                write!(f, "Tam2 on {} cannot be captured", serialize_coord(square))
+            }
+            ReplayErrorKind::CannotCaptureOwnPiece(square) => write!(
+                f,
+                "the piece on {} is of the side that moves",
+                serialize_coord(square)
+            ),
+            ReplayErrorKind::Occupied(square) => {
+                write!(f, "{} is occupied", serialize_coord(square))
+            }
+            ReplayErrorKind::NotInHand { color, prof } => write!(
+                f,
+                "{}{} is not in the hand of the side to move",
+                color.map_or("", serialize_color),
+                serialize_prof(prof)
+            ),
+            ReplayErrorKind::AmbiguousColor(prof) => write!(
+                f,
+                "the hand has {} in both colours, so its colour cannot be left out",
+                serialize_prof(prof)
+            ),
+            ReplayErrorKind::AmbiguousTamPath(src) => write!(
+                f,
+                "more than one way fits the squares written for Tam2 from {}",
+                serialize_coord(src)
+            ),
+            ReplayErrorKind::NoTamPath(src) => write!(
+                f,
+                "no way fits the squares written for Tam2 from {}",
+                serialize_coord(src)
+            ),
+            ReplayErrorKind::TamMovedTwiceInARow(src) => write!(
+                f,
+                "Tam2 on {} is moved right after it was moved, which the rules in use forbid",
+                serialize_coord(src)
+            ),
+        }
+    }
+}
+
+impl State {
+    /// The standard initial position, with empty hands.
+    #[must_use]
+    pub fn initial() -> Self {
+        State {
+            field: Field {
+                board: yhuap_initial_board(),
+                a_side_hop1zuo1: vec![],
+                ia_side_hop1zuo1: vec![],
+            },
+            side_to_move: None,
+            tam_moved_last: false,
+        }
+    }
+
+    /// Applies a single move. A move whose stick failed leaves the piece where it was, and only passes the turn.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::body::movement::parse;
+    /// use cetkaik_kiaak::replay::State;
+    /// use cetkaik_core::absolute::{Coord, Row, Column, Side};
+    /// let state = State::initial()
+    ///     .apply_move(&parse("LAU弓LAILY橋或此無").unwrap().1)
+    ///     .unwrap();
+    /// assert_eq!(state.field, State::initial().field);
+    /// assert_eq!(state.side_to_move, Some(Side::ASide));
+    ///
+    /// let state = state.apply_move(&parse("LE弓LILU橋二").unwrap().1).unwrap();
+    /// assert!(state.field.board.contains_key(&Coord(Row::U, Column::L)));
+    /// assert_eq!(state.side_to_move, Some(Side::IASide));
+    /// ```
+    pub fn apply_move(&self, m: &Move) -> Result<Self, ReplayErrorKind> {
+        self.apply_move_with(m, Config::standard())
+    }
+
+    /// Applies a single move under the given rules.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::body::movement::parse;
+    /// use cetkaik_kiaak::header::keys::Rule;
+    /// use cetkaik_kiaak::replay::{ReplayErrorKind, State};
+    /// use cetkaik_kiaak::rule::Config;
+    /// use cetkaik_core::absolute::{Coord, Row, Column};
+    /// let config = Config::of(&Rule::HardTam2Power).unwrap();
+    /// let state = State::initial()
+    ///     .apply_move_with(&parse("ZO皇[ZU]ZY").unwrap().1, config)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     state.apply_move_with(&parse("ZY皇[ZU]ZO").unwrap().1, config),
+    ///     Err(ReplayErrorKind::TamMovedTwiceInARow(Coord(Row::Y, Column::Z)))
+    /// );
+    /// assert!(state.apply_move(&parse("ZY皇[ZU]ZO").unwrap().1).is_ok());
+    /// ```
+    pub fn apply_move_with(&self, m: &Move, config: Config) -> Result<Self, ReplayErrorKind> {
+        match *m {
+            Move::NoStepAndNoStick { src, prof, dest } => {
+                self.move_piece(src, prof, None, dest, true)
+            }
+            Move::NoStepAndWaterStick {
+                src,
+                prof,
+                dest,
+                water_stick_successful,
+                ..
+            } => self.move_piece(src, prof, None, dest, water_stick_successful),
+            Move::StepAndNoStick {
+                src,
+                prof,
+                step,
+                dest,
+            } => self.move_piece(src, prof, Some(step), dest, true),
+            Move::StepAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                water_stick_successful,
+                ..
+            }
+            | Move::StepAndBridgeStickAndWaterStick {
+                src,
+                prof,
+                step,
+                dest,
+                water_stick_successful,
+                ..
+            } => self.move_piece(src, prof, Some(step), dest, water_stick_successful),
+            Move::StepAndBridgeStick {
+                src,
+                prof,
+                step,
+                dest,
+                bridge_stick_successful,
+                ..
+            } => self.move_piece(src, prof, Some(step), dest, bridge_stick_successful),
+            Move::TamNoStep {
+                src,
+                first_dest,
+                second_dest,
+            } => self.move_tam(src, None, first_dest, second_dest, config),
+            Move::TamStepUnspecified {
+                src,
+                step,
+                second_dest,
+            } => self.move_tam(src, Some(step), None, second_dest, config),
+            Move::TamStepDuringFormer {
+                src,
+                step,
+                first_dest,
+                second_dest,
+            }
+            | Move::TamStepDuringLatter {
+                src,
+                first_dest,
+                step,
+                second_dest,
+            } => self.move_tam(src, Some(step), first_dest, second_dest, config),
+            Move::Parachute { color, prof, dest } => self.parachute(color, prof, dest),
+        }
+    }
+
+    /// Applies a body element. Elements other than moves leave the state as it is,
+    /// except that `終季` starts the next season from the initial position.
+    pub fn apply_elem(&self, elem: &Elem) -> Result<Self, ReplayErrorKind> {
+        self.apply_elem_with(elem, Config::standard())
+    }
+
+    /// Applies a body element under the given rules.
+    pub fn apply_elem_with(&self, elem: &Elem, config: Config) -> Result<Self, ReplayErrorKind> {
+        match elem {
+            Elem::Move(m, _) => self.apply_move_with(m, config),
+            Elem::TaXotTyMok(_, Action::TaXot(_)) => Ok(State::initial()),
+            Elem::TaXotTyMok(_, Action::TyMok)
+            | Elem::CaptureComment(..)
+            | Elem::SeasonEnd(_)
+            | Elem::GameEnd
+            | Elem::Points(..) => Ok(self.clone()),
+        }
+    }
+
+    /// Fills in what the record leaves out but the position determines:
+    /// the profession written as `片`, the colour of a parachute written as `或`,
+    /// the first destination of Tam2 written as `[或]` or left out,
+    /// and whether Tam2 written with two squares steps during the former or the latter stage.
+    /// A profession that does not match the piece on the source square is an error,
+    /// and so is a Tam2 move that fits no way, or more than one way, between the squares written.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::body::movement::{parse, Move};
+    /// use cetkaik_kiaak::replay::{ReplayErrorKind, State};
+    /// use cetkaik_core::absolute::{Coord, Row, Column};
+    /// use cetkaik_core::Profession;
+    /// let state = State::initial();
+    /// assert_eq!(
+    ///     state.resolve_move(&parse("XI片XU無撃裁").unwrap().1),
+    ///     Ok(parse("XI兵XU無撃裁").unwrap().1)
+    /// );
+    /// assert_eq!(
+    ///     state.resolve_move(&parse("XI弓XU無撃裁").unwrap().1),
+    ///     Err(ReplayErrorKind::ProfessionMismatch {
+    ///         square: Coord(Row::I, Column::X),
+    ///         written: Profession::Gua2,
+    ///         actual: Profession::Kauk2,
+    ///     })
+    /// );
+    /// let mut without_ni = state.clone();
+    /// without_ni.field.board.remove(&Coord(Row::I, Column::N));
+    /// assert_eq!(
+    ///     without_ni.resolve_move(&parse("ZO皇[或]NI").unwrap().1),
+    ///     Ok(parse("ZO皇[TU]NI").unwrap().1)
+    /// );
+    /// /* ZO皇TU can go through either TO or ZU */
+    /// assert_eq!(
+    ///     state.resolve_move(&parse("ZO皇TU").unwrap().1),
+    ///     Err(ReplayErrorKind::AmbiguousTamPath(Coord(Row::O, Column::Z)))
+    /// );
+    /// /* TU is the only square next to both ZO and NI */
+    /// assert_eq!(
+    ///     state.resolve_move(&parse("ZO皇NINE").unwrap().1),
+    ///     Ok(parse("ZO皇[TU]NINE").unwrap().1)
+    /// );
+    /// ```
+    pub fn resolve_move(&self, m: &Move) -> Result<Move, ReplayErrorKind> {
+        let mut m = m.clone();
+        match &mut m {
+            Move::NoStepAndNoStick { src, prof, .. }
+            | Move::NoStepAndWaterStick { src, prof, .. }
+            | Move::StepAndNoStick { src, prof, .. }
+            | Move::StepAndWaterStick { src, prof, .. }
+            | Move::StepAndBridgeStick { src, prof, .. }
+            | Move::StepAndBridgeStickAndWaterStick { src, prof, .. } => {
+                *prof = Some(self.profession_at(*src, *prof)?);
+            }
+            Move::TamNoStep {
+                first_dest: Some(_),
+                ..
+            }
+            | Move::TamStepDuringFormer {
+                first_dest: Some(_),
+                ..
+            }
+            | Move::TamStepDuringLatter {
+                first_dest: Some(_),
+                ..
+            } => {}
+            Move::TamNoStep {
+                src,
+                first_dest,
+                second_dest,
+            } => {
+                *first_dest = Some(only_tam_path(
+                    *src,
+                    self.tam_waypoints(*src, *src, *second_dest),
+                )?);
+            }
+            Move::TamStepDuringFormer {
+                src,
+                step,
+                first_dest,
+                second_dest,
+            } => {
+                *first_dest = Some(only_tam_path(
+                    *src,
+                    self.tam_waypoints(*src, *step, *second_dest),
+                )?);
+            }
+            Move::TamStepDuringLatter {
+                src,
+                first_dest,
+                step,
+                ..
+            } => {
+                *first_dest = Some(only_tam_path(*src, self.tam_waypoints(*src, *src, *step))?);
+            }
+            Move::TamStepUnspecified {
+                src,
+                step,
+                second_dest,
+            } => {
+                let (src, step, second_dest) = (*src, *step, *second_dest);
+                /* the stepped square is next to where the stage stepping on it begins, and the square it ends on is next to it */
+                let former = self
+                    .tam_waypoints(src, step, second_dest)
+                    .filter(|_| distance(src, step) == 1)
+                    .map(|first_dest| Move::TamStepDuringFormer {
+                        src,
+                        step,
+                        first_dest: Some(first_dest),
+                        second_dest,
+                    });
+                let latter = self
+                    .tam_waypoints(src, src, step)
+                    .filter(|_| distance(step, second_dest) == 1)
+                    .map(|first_dest| Move::TamStepDuringLatter {
+                        src,
+                        first_dest: Some(first_dest),
+                        step,
+                        second_dest,
+                    });
+                m = only_tam_path(src, former.chain(latter))?;
+            }
+            Move::Parachute { color, prof, .. } => {
+                let (resolved, _) = self.parachute_source(*color, *prof)?;
+                *color = Some(resolved);
+            }
+        }
+        Ok(m)
+    }
+
+    fn profession_at(
+        &self,
+        src: Coord,
+        written: PossiblyUnknown<Profession>,
+    ) -> Result<Profession, ReplayErrorKind> {
+        match self.field.board.get(&src) {
+            None => Err(ReplayErrorKind::NoPieceAt(src)),
+            Some(Piece::Tam2) => Err(ReplayErrorKind::UnexpectedTam(src)),
+            Some(Piece::NonTam2Piece { prof: actual, .. }) => match written {
+                Some(written) if written != *actual => Err(ReplayErrorKind::ProfessionMismatch {
+                    square: src,
+                    written,
+                    actual: *actual,
+                }),
+                _ => Ok(*actual),
+            },
+        }
+    }
+
+    /// The squares that Tam2 at `src` can stop at after leaving `from` and before heading to `to`.
+    /// Each of the two stages of Tam2 ends next to where it begins, on an empty square.
+    fn tam_waypoints(
+        &self,
+        src: Coord,
+        from: Coord,
+        to: Coord,
+    ) -> impl Iterator<Item = Coord> + '_ {
+        ALL_SQUARES.iter().copied().filter(move |c| {
+            distance(from, *c) == 1
+                && distance(*c, to) == 1
+                && (*c == src || !self.field.board.contains_key(c))
+        })
+    }
+
+    fn move_piece(
+        &self,
+        src: Coord,
+        prof: PossiblyUnknown<Profession>,
+        step: Option<Coord>,
+        dest: Coord,
+        successful: bool,
+    ) -> Result<Self, ReplayErrorKind> {
+        let piece = *self
+            .field
+            .board
+            .get(&src)
+            .ok_or(ReplayErrorKind::NoPieceAt(src))?;
+        self.profession_at(src, prof)?;
+        let mover = match piece {
+            Piece::Tam2 => return Err(ReplayErrorKind::UnexpectedTam(src)),
+            Piece::NonTam2Piece { side, .. } => side,
+        };
+        if self.side_to_move.is_some_and(|side| side != mover) {
+            return Err(ReplayErrorKind::WrongSide(src));
+        }
+        if let Some(step) = step {
+            if !self.field.board.contains_key(&step) {
+                return Err(ReplayErrorKind::NothingToStepOn(step));
+            }
+        }
+
+        let mut next = self.clone();
+        next.side_to_move = Some(!mover);
+        next.tam_moved_last = false;
+        if !successful {
+            return Ok(next);
+        }
+
+        next.field.board.remove(&src);
+        match next.field.board.get(&dest) {
+            None => {}
+            Some(Piece::Tam2) => return Err(ReplayErrorKind::CannotCaptureTam(dest)),
+            Some(Piece::NonTam2Piece { side, .. }) if *side == mover => {
+                return Err(ReplayErrorKind::CannotCaptureOwnPiece(dest))
+            }
+            Some(Piece::NonTam2Piece { color, prof, .. }) => {
+                let (color, prof) = (*color, *prof);
+                next.field
+                    .insert_nontam_piece_into_hop1zuo1(color, prof, mover);
+            }
+        }
+        next.field.board.insert(dest, piece);
+        Ok(next)
+    }
+
+    /// Tam2 moves twice; the first destination, if known, must be empty, and so must the second one.
+    fn move_tam(
+        &self,
+        src: Coord,
+        step: Option<Coord>,
+        first_dest: PossiblyUnknown<Coord>,
+        second_dest: Coord,
+        config: Config,
+    ) -> Result<Self, ReplayErrorKind> {
+        if self.field.board.get(&src) != Some(&Piece::Tam2) {
+            return Err(ReplayErrorKind::NotTam(src));
+        }
+        if self.tam_moved_last && !config.tam_may_move_twice_in_a_row {
+            return Err(ReplayErrorKind::TamMovedTwiceInARow(src));
+        }
+        if let Some(step) = step {
+            if step == src || !self.field.board.contains_key(&step) {
+                return Err(ReplayErrorKind::NothingToStepOn(step));
+            }
+        }
+
+        let mut next = self.clone();
+        next.field.board.remove(&src);
+        for dest in first_dest.iter().chain(std::iter::once(&second_dest)) {
+            if next.field.board.contains_key(dest) {
+                return Err(ReplayErrorKind::Occupied(*dest));
+            }
+        }
+        next.field.board.insert(second_dest, Piece::Tam2);
+        next.side_to_move = self.side_to_move.map(|side| !side);
+        next.tam_moved_last = true;
+        Ok(next)
+    }
+
+    fn parachute(
+        &self,
+        color: PossiblyUnknown<Color>,
+        prof: Profession,
+        dest: Coord,
+    ) -> Result<Self, ReplayErrorKind> {
+        if self.field.board.contains_key(&dest) {
+            return Err(ReplayErrorKind::Occupied(dest));
+        }
+        let (color, mover) = self.parachute_source(color, prof)?;
+        let mut field = self
+            .field
+            .find_and_remove_piece_from_hop1zuo1(color, prof, mover)
+            .ok_or(ReplayErrorKind::NotInHand {
+                color: Some(color),
+                prof,
+            })?;
+        field.board.insert(
+            dest,
+            Piece::NonTam2Piece {
+                color,
+                prof,
+                side: mover,
+            },
+        );
+        Ok(State {
+            field,
+            side_to_move: Some(!mover),
+            tam_moved_last: false,
+        })
+    }
+
+    /// The colour of the piece to be parachuted and the side whose hand it comes from.
+    /// If it is not known whose turn it is, the piece is taken from whichever hand has it.
+    /// An unknown colour is an error unless the hand has the profession in only one colour.
+    fn parachute_source(
+        &self,
+        color: PossiblyUnknown<Color>,
+        prof: Profession,
+    ) -> Result<(Color, Side), ReplayErrorKind> {
+        let sides = match self.side_to_move {
+            Some(side) => vec![side],
+            None => vec![Side::ASide, Side::IASide],
+        };
+        let colors = match color {
+            Some(color) => vec![color],
+            None => vec![Color::Huok2, Color::Kok1],
+        };
+        let candidates: Vec<(Color, Side)> = sides
+            .into_iter()
+            .flat_map(|side| colors.iter().map(move |color| (*color, side)))
+            .filter(|(color, side)| {
+                let hand = match side {
+                    Side::ASide => &self.field.a_side_hop1zuo1,
+                    Side::IASide => &self.field.ia_side_hop1zuo1,
+                };
+                hand.contains(&NonTam2Piece {
+                    color: *color,
+                    prof,
+                })
+            })
+            .collect();
+        match candidates.as_slice() {
+            [] => Err(ReplayErrorKind::NotInHand { color, prof }),
+            [(first, _), rest @ ..] if rest.iter().any(|(other, _)| other != first) => {
+                Err(ReplayErrorKind::AmbiguousColor(prof))
+            }
+            [first, ..] => Ok(*first),
+        }
+    }
+}
+
+/// The only one of the ways that Tam2 at `src` can go.
+fn only_tam_path<T>(
+    src: Coord,
+    mut candidates: impl Iterator<Item = T>,
+) -> Result<T, ReplayErrorKind> {
+    match (candidates.next(), candidates.next()) {
+        (Some(path), None) => Ok(path),
+        (None, _) => Err(ReplayErrorKind::NoTamPath(src)),
+        (Some(_), Some(_)) => Err(ReplayErrorKind::AmbiguousTamPath(src)),
+    }
+}
+
+/// An iterator over the states after each element of a body. See [`replay`].
+#[derive(Clone, Debug)]
+pub struct Replay<'a> {
+    elems: std::iter::Enumerate<std::slice::Iter<'a, (Elem, Option<Punctuation>)>>,
+    state: State,
+    config: Config,
+    failed: bool,
+}
+
+/// Replays the body from the standard initial position, yielding the state after each element of `Body.0`.
+/// Once a move fails to apply, the error is yielded and the iteration stops.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::replay::replay;
+/// let (_, body) = parse_ckka("[SY] [JV]\nXI兵XU無撃裁 XAI兵XY無撃裁\nXU兵XY無撃裁 手兵\n").unwrap();
+/// let states = replay(&body).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(states.len(), 4);
+/// assert_eq!(states[2].field.a_side_hop1zuo1.len(), 1);
+/// ```
+#[must_use]
+pub fn replay(body: &Body) -> Replay<'_> {
+    replay_with(body, Config::standard())
+}
+
+/// Replays the body under the given rules. See [`replay`].
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::replay::replay_with;
+/// use cetkaik_kiaak::rule::Config;
+/// let (header, body) = parse_ckka("{律:硬皇力}\nZO皇[ZU]ZY ZY皇[ZU]ZO\n").unwrap();
+/// let config = Config::from_header(&header).unwrap();
+/// let states: Vec<_> = replay_with(&body, config).collect();
+/// assert!(states[0].is_ok());
+/// assert_eq!(states[1].as_ref().unwrap_err().index, 1);
+/// ```
+#[must_use]
+pub fn replay_with(body: &Body, config: Config) -> Replay<'_> {
+    Replay {
+        elems: body.0.iter().enumerate(),
+        state: State::initial(),
+        config,
+        failed: false,
+    }
+}
+
+impl Iterator for Replay<'_> {
+    type Item = Result<State, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let (index, (elem, _)) = self.elems.next()?;
+        match self.state.apply_elem_with(elem, self.config) {
+            Ok(state) => self.state = state,
+            Err(kind) => {
+                self.failed = true;
+                return Some(Err(ReplayError { index, kind }));
+            }
+        }
+        Some(Ok(self.state.clone()))
+    }
+}
+
+/// Replays the body and fills in each move as `State::resolve_move` does.
+/// The result has the same elements as `body`, so the indices of `Body.0` stay the same.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::replay::resolve;
+/// let (_, body) = parse_ckka("[SY] [JV]\nXI片XU無撃裁 XAI片XY無撃裁\n").unwrap();
+/// let (_, expected) = parse_ckka("[SY] [JV]\nXI兵XU無撃裁 XAI兵XY無撃裁\n").unwrap();
+/// assert_eq!(resolve(&body), Ok(expected));
+/// ```
+pub fn resolve(body: &Body) -> Result<Body, ReplayError> {
+    resolve_with(body, Config::standard())
+}
+
+/// Replays the body under the given rules and fills in each move. See [`resolve`].
+pub fn resolve_with(body: &Body, config: Config) -> Result<Body, ReplayError> {
+    let mut state = State::initial();
+    let mut elems = Vec::with_capacity(body.0.len());
+    for (index, (elem, punctuation)) in body.0.iter().enumerate() {
+        let elem = match elem {
+            Elem::Move(m, comment) => Elem::Move(
+                state
+                    .resolve_move(m)
+                    .map_err(|kind| ReplayError { index, kind })?,
+                comment.clone(),
+            ),
+            elem => elem.clone(),
+        };
+        state = state
+            .apply_elem_with(&elem, config)
+            .map_err(|kind| ReplayError { index, kind })?;
+        elems.push((elem, *punctuation));
+    }
+    Ok(Body(elems))
+}
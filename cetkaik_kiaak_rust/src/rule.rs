@@ -0,0 +1,66 @@
+use super::header::keys::{Rule, ValueError};
+use super::header::Header;
+
+/// The rules under which a game is replayed and its moves are checked.
+/// `Config::default()` gives the standard rules, which are assumed when the header names none.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Config {
+    /// Whether Tam2 may be moved right after the other player has moved it
+    pub tam_may_move_twice_in_a_row: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::standard()
+    }
+}
+
+impl Config {
+    /// The standard rules.
+    #[must_use]
+    pub const fn standard() -> Self {
+        Config {
+            tam_may_move_twice_in_a_row: true,
+        }
+    }
+
+    /// The rules named by `{律:...}`, or `None` for a rule set that this crate does not know.
+    /// Under `硬皇力`, Tam2 cannot be moved on two turns in a row.
+    #[must_use]
+    pub const fn of(rule: &Rule) -> Option<Self> {
+        match rule {
+            Rule::HardTam2Power => Some(Config {
+                tam_may_move_twice_in_a_row: false,
+            }),
+            Rule::Other(_) => None,
+        }
+    }
+
+    /// The rules the header names, or the standard rules if it names none.
+    /// A rule set that this crate does not know is an error rather than being taken for the standard rules,
+    /// since its moves and sticks may differ from them in ways that this crate cannot tell.
+    ///
+    /// Examples:
+    /// ```
+    /// use cetkaik_kiaak::parse_ckka;
+    /// use cetkaik_kiaak::rule::Config;
+    /// let (header, _) = parse_ckka("{律:硬皇力}\nXU兵XY無撃裁\n").unwrap();
+    /// assert!(!Config::from_header(&header).unwrap().tam_may_move_twice_in_a_row);
+    /// let (header, _) = parse_ckka("XU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(Config::from_header(&header), Ok(Config::standard()));
+    /// let (header, _) = parse_ckka("{律:軟皇力}\nXU兵XY無撃裁\n").unwrap();
+    /// assert_eq!(
+    ///     Config::from_header(&header).unwrap_err().to_string(),
+    ///     "cannot read `軟皇力` as the value of `律`"
+    /// );
+    /// ```
+    pub fn from_header(header: &Header) -> Result<Self, ValueError> {
+        match header.rule() {
+            None => Ok(Config::standard()),
+            Some(rule) => Config::of(&rule).ok_or(ValueError {
+                key: "律",
+                value: header.get("律").unwrap_or_default().to_owned(),
+            }),
+        }
+    }
+}
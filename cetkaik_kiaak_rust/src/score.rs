@@ -0,0 +1,202 @@
+use super::body::{Action, Body, Elem};
+use super::header::{Header, PlayerAndPoint};
+use super::replay::{replay, State};
+use super::season::Payouts;
+use std::fmt;
+
+/// The points of both players, in the order the header lists them.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Standing {
+    /// The index in `Body.0` of the taxot after which the points are these
+    pub index: usize,
+    pub points: [i64; 2],
+}
+
+/// How the game came to an end.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum GameEnd {
+    /// `星一周` is written
+    Declared,
+
+    /// The player at this index in the header has no points left
+    OutOfPoints(usize),
+}
+
+/// Something about the points that the ledger cannot make sense of.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum ScoreIssue {
+    /// A player named in the body is neither of the players in the header
+    UnknownPlayer(String),
+
+    /// A taxot does not say who declares it, so the points are left as they were
+    UnnamedPlayer,
+
+    /// A taxot does not say how many points it moves, and they cannot be worked out
+    /// since the body cannot be replayed up to it, so the points are left as they were
+    UnknownPayout,
+
+    /// A line of points disagrees with the points computed so far
+    Mismatch {
+        written: [i64; 2],
+        computed: [i64; 2],
+    },
+
+    /// A taxot comes after the game has ended
+    AfterGameEnd,
+}
+
+/// An issue found in a body, together with the index of the element in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ScoreError {
+    pub index: usize,
+    pub issue: ScoreIssue,
+}
+
+impl fmt::Display for ScoreIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreIssue::UnknownPlayer(name) => {
+                write!(f, "{name} is neither of the players in the header")
+            }
+            ScoreIssue::UnnamedPlayer => write!(f, "the taxot does not say who declares it"),
+            ScoreIssue::UnknownPayout => write!(
+                f,
+                "the points of the taxot are not written and cannot be worked out"
+            ),
+            ScoreIssue::Mismatch { written, computed } => write!(
+                f,
+                "the points are written as {} and {}, but come to {} and {}",
+                written[0], written[1], computed[0], computed[1]
+            ),
+            ScoreIssue::AfterGameEnd => write!(f, "a taxot comes after the game has ended"),
+        }
+    }
+}
+
+/// The points of the players throughout the game.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Ledger {
+    pub player_names: [String; 2],
+
+    /// The points at the start of the game
+    pub initial: [i64; 2],
+
+    /// The points after each taxot
+    pub standings: Vec<Standing>,
+
+    /// Where and how the game ended, if it did
+    pub end: Option<(usize, GameEnd)>,
+
+    pub errors: Vec<ScoreError>,
+}
+
+impl Ledger {
+    /// The points after the last taxot.
+    #[must_use]
+    pub fn current(&self) -> [i64; 2] {
+        self.standings
+            .last()
+            .map_or(self.initial, |standing| standing.points)
+    }
+
+    fn player_index(&self, name: &str) -> Option<usize> {
+        self.player_names.iter().position(|n| n == name)
+    }
+
+    fn push_error(&mut self, index: usize, issue: ScoreIssue) {
+        self.errors.push(ScoreError { index, issue });
+    }
+
+    /// The players may be written in either order.
+    fn check_points(&mut self, index: usize, q1: &PlayerAndPoint, q2: &PlayerAndPoint) {
+        let mut written = self.current();
+        for q in &[q1, q2] {
+            let Some(i) = self.player_index(&q.player_name) else {
+                let name = q.player_name.clone();
+                self.push_error(index, ScoreIssue::UnknownPlayer(name));
+                return;
+            };
+            written[i] = q.point;
+        }
+        let computed = self.current();
+        if written != computed {
+            self.push_error(index, ScoreIssue::Mismatch { written, computed });
+        }
+    }
+}
+
+/// Follows the points of the players from those in the header, moving the points of each taxot to the player who declares it.
+/// A taxot that does not write its points moves the payout worked out from the declared hands,
+/// as in [`track_seasons`](crate::season::track_seasons).
+/// Every line of points in the body is checked against the points computed up to there.
+/// Returns `None` if the header names no players, since then nobody can be credited.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::score::{ledger, GameEnd};
+/// let (header, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+/// let ledger = ledger(&header, &body).unwrap();
+/// assert_eq!(ledger.current(), [40, 0]);
+/// assert_eq!(ledger.end, Some((body.0.len() - 2, GameEnd::OutOfPoints(1))));
+/// assert_eq!(ledger.errors, vec![]);
+/// ```
+#[must_use]
+pub fn ledger(header: &Header, body: &Body) -> Option<Ledger> {
+    let (p1, p2) = header.players.as_ref()?;
+    let mut ledger = Ledger {
+        player_names: [p1.player_name.clone(), p2.player_name.clone()],
+        initial: [p1.point, p2.point],
+        standings: vec![],
+        end: None,
+        errors: vec![],
+    };
+
+    /* the states after a move that cannot be replayed are unknown */
+    let states = replay(body).map(Result::ok).chain(std::iter::repeat(None));
+    let mut before = Some(State::initial());
+    let mut payouts = Payouts::default();
+    for (index, (elem, after)) in body.elems().zip(states).enumerate() {
+        let computed = match (&before, &after) {
+            (Some(before), Some(after)) => payouts.update(elem, before, after),
+            _ => None,
+        };
+        before = after;
+        match elem {
+            Elem::TaXotTyMok(hand_creation, Action::TaXot(written)) => {
+                if ledger.end.is_some() {
+                    ledger.push_error(index, ScoreIssue::AfterGameEnd);
+                    continue;
+                }
+                let Some(name) = &hand_creation.player_name else {
+                    ledger.push_error(index, ScoreIssue::UnnamedPlayer);
+                    continue;
+                };
+                let Some(winner) = ledger.player_index(name) else {
+                    ledger.push_error(index, ScoreIssue::UnknownPlayer(name.clone()));
+                    continue;
+                };
+                let Some(payout) = written.or(computed) else {
+                    ledger.push_error(index, ScoreIssue::UnknownPayout);
+                    continue;
+                };
+                let mut points = ledger.current();
+                points[winner] += payout;
+                points[1 - winner] -= payout;
+                ledger.standings.push(Standing { index, points });
+                if points[1 - winner] <= 0 {
+                    ledger.end = Some((index, GameEnd::OutOfPoints(1 - winner)));
+                }
+            }
+            Elem::GameEnd => {
+                ledger.end.get_or_insert((index, GameEnd::Declared));
+            }
+            Elem::Points(q1, q2) => ledger.check_points(index, q1, q2),
+            Elem::Move(..)
+            | Elem::CaptureComment(..)
+            | Elem::TaXotTyMok(_, Action::TyMok)
+            | Elem::SeasonEnd(_) => {}
+        }
+    }
+    Some(ledger)
+}
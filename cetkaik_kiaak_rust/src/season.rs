@@ -0,0 +1,217 @@
+use super::body::{Action, Body, Elem, Season};
+use super::declaration::Declarations;
+use super::replay::{replay, ReplayError, State};
+use super::serialize::serialize_season;
+use std::fmt;
+
+impl Season {
+    /// The season that follows, or `None` after winter.
+    /// `上季` and `下季` name the first and the second season of a game that lasts two seasons.
+    #[must_use]
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Season::Spring => Some(Season::Summer),
+            Season::Summer => Some(Season::Fall),
+            Season::Fall => Some(Season::Winter),
+            Season::Up => Some(Season::Down),
+            Season::Winter | Season::Down => None,
+        }
+    }
+}
+
+/// The rate by which the points of the hands are multiplied, which doubles after each tymok in the season.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::season::rate;
+/// assert_eq!(rate(0), 1);
+/// assert_eq!(rate(3), 8);
+/// ```
+#[must_use]
+pub const fn rate(ty_mok_count: u32) -> i64 {
+    1 << ty_mok_count
+}
+
+/// What each taxot pays out, worked out as the body is replayed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Payouts {
+    declarations: Declarations,
+    ty_mok_count: u32,
+}
+
+impl Payouts {
+    /// The number of tymoks so far in the season.
+    pub(crate) const fn ty_mok_count(&self) -> u32 {
+        self.ty_mok_count
+    }
+
+    /// Takes in an element, given the states before and after it, and returns what it pays out if it is a taxot.
+    /// The points of the hands declared in the season, and still made, are multiplied by the [`rate`],
+    /// but points that the record writes for the hands are paid as they are written:
+    /// in example 5 of the README, 獣 and then 行行 across a `再行` pay 三 and 五, 八 in all.
+    pub(crate) fn update(&mut self, elem: &Elem, before: &State, after: &State) -> Option<i64> {
+        let declaration = self.declarations.update(elem, before, after);
+        match elem {
+            Elem::TaXotTyMok(_, Action::TyMok) => {
+                self.ty_mok_count += 1;
+                None
+            }
+            Elem::TaXotTyMok(_, Action::TaXot(_)) => {
+                let payout = declaration.map_or(0, |declaration| {
+                    declaration.written_points + declaration.points * rate(self.ty_mok_count)
+                });
+                self.ty_mok_count = 0;
+                Some(payout)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A season that has ended with a taxot.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct SeasonRecord {
+    pub season: Season,
+
+    /// The index in `Body.0` of the taxot that ended the season
+    pub ta_xot: usize,
+
+    pub ty_mok_count: u32,
+
+    /// The points of the hands declared in the season by the player who declared the taxot, multiplied by the rate
+    pub payout: i64,
+}
+
+/// Something about the progression of seasons that contradicts the record.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum SeasonWarning {
+    /// A season-end comment names a season other than the one that has just ended
+    WrongSeasonEnd { written: Season, actual: Season },
+
+    /// A season-end comment appears where no season has just ended
+    NoSeasonToEnd,
+
+    /// The points written after `終季` differ from the payout computed from the hands and the rate
+    PayoutMismatch { written: i64, computed: i64 },
+
+    /// A taxot appears after the last season has ended
+    AfterLastSeason,
+}
+
+/// A warning found in a body, together with the index of the element in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct SeasonError {
+    pub index: usize,
+    pub warning: SeasonWarning,
+}
+
+impl fmt::Display for SeasonWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SeasonWarning::WrongSeasonEnd { written, actual } => write!(
+                f,
+                "`{}終` is written, but the season that has just ended is {}",
+                serialize_season(written),
+                serialize_season(actual)
+            ),
+            SeasonWarning::NoSeasonToEnd => write!(f, "no season has just ended"),
+            SeasonWarning::PayoutMismatch { written, computed } => write!(
+                f,
+                "{written} points are written, but the hands and the rate give {computed}"
+            ),
+            SeasonWarning::AfterLastSeason => {
+                write!(f, "a taxot comes after the last season has ended")
+            }
+        }
+    }
+}
+
+/// The seasons played in a body.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Seasons {
+    pub records: Vec<SeasonRecord>,
+    pub warnings: Vec<SeasonError>,
+}
+
+/// Replays the body, starting from `first`, to find which season each taxot ends and how many points it moves.
+/// Each season-end comment is checked against the season that has just ended.
+/// The payout is the sum of the points of the hands the declaring player has declared in the season and still has made,
+/// multiplied by the [`rate`], except that points the record writes for the hands, as in `而手三`, are taken as they are.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::body::Season;
+/// use cetkaik_kiaak::season::track_seasons;
+/// let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+/// let seasons = track_seasons(&body, Season::Spring).unwrap();
+/// assert_eq!(seasons.records[0].season, Season::Spring);
+/// assert_eq!(seasons.records[0].ty_mok_count, 1);
+/// /* 同色獣 and 王, doubled by the 再行 */
+/// assert_eq!(seasons.records[0].payout, 20);
+/// ```
+pub fn track_seasons(body: &Body, first: Season) -> Result<Seasons, ReplayError> {
+    let mut records: Vec<SeasonRecord> = vec![];
+    let mut warnings = vec![];
+    let mut current = Some(first);
+    let mut just_ended: Option<(Season, usize)> = None;
+    let mut before = State::initial();
+    let mut payouts = Payouts::default();
+
+    for (index, (elem, state)) in body.elems().zip(replay(body)).enumerate() {
+        let state = state?;
+        let ty_mok_count = payouts.ty_mok_count();
+        let payout = payouts.update(elem, &before, &state);
+        let mut warn = |warning| warnings.push(SeasonError { index, warning });
+        match elem {
+            Elem::TaXotTyMok(_, Action::TaXot(written)) => match current {
+                None => warn(SeasonWarning::AfterLastSeason),
+                Some(season) => {
+                    let payout = payout.unwrap_or_default();
+                    match *written {
+                        Some(written) if written != payout => {
+                            warn(SeasonWarning::PayoutMismatch {
+                                written,
+                                computed: payout,
+                            });
+                        }
+                        _ => {}
+                    }
+                    just_ended = Some((season, records.len()));
+                    records.push(SeasonRecord {
+                        season,
+                        ta_xot: index,
+                        ty_mok_count,
+                        payout,
+                    });
+                    current = season.next();
+                }
+            },
+            Elem::SeasonEnd(written) => match just_ended {
+                Some((actual, ordinal)) if names_season(*written, actual, ordinal) => {}
+                Some((actual, _)) => warn(SeasonWarning::WrongSeasonEnd {
+                    written: *written,
+                    actual,
+                }),
+                None => warn(SeasonWarning::NoSeasonToEnd),
+            },
+            Elem::Move(..) => just_ended = None,
+            Elem::TaXotTyMok(_, Action::TyMok)
+            | Elem::CaptureComment(..)
+            | Elem::GameEnd
+            | Elem::Points(..) => {}
+        }
+        before = state;
+    }
+    Ok(Seasons { records, warnings })
+}
+
+/// Whether `written` names the season `actual`, which is the `ordinal`-th (0-based) season of the game.
+/// `上季` and `下季` name the first and the second season, whichever they are.
+fn names_season(written: Season, actual: Season, ordinal: usize) -> bool {
+    match written {
+        Season::Up => ordinal == 0,
+        Season::Down => ordinal == 1,
+        written => written == actual,
+    }
+}
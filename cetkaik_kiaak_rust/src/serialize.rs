@@ -0,0 +1,1223 @@
+use super::body::hand::Hand;
+use super::body::movement::{Move, PossiblyUnknown};
+use super::body::{notation1, notation2, notation3, Action, Body, Elem, HandCreation, Season};
+use super::header::{self, Header, PlayerAndPoint};
+use super::replay::State;
+use super::rule::Config;
+use cetkaik_core::absolute::{serialize_coord, Coord};
+use cetkaik_core::{serialize_color, serialize_prof, Color, Profession};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// How numerals such as points are written.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum NumeralStyle {
+    /// `20`, `-5`
+    Arabic,
+
+    /// `二十`, `下五`
+    Pekzep,
+}
+
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::serialize::{serialize_numeral, NumeralStyle};
+/// assert_eq!(serialize_numeral(20, NumeralStyle::Arabic), "20");
+/// assert_eq!(serialize_numeral(20, NumeralStyle::Pekzep), "二十");
+/// assert_eq!(serialize_numeral(0, NumeralStyle::Pekzep), "無");
+/// assert_eq!(serialize_numeral(-5, NumeralStyle::Pekzep), "下五");
+/// assert_eq!(serialize_numeral(125, NumeralStyle::Pekzep), "百二五");
+/// assert_eq!(serialize_numeral(5678, NumeralStyle::Pekzep), "五六百七八");
+/// assert_eq!(serialize_numeral(876_543, NumeralStyle::Pekzep), "八十七万六五百四三");
+/// assert_eq!(serialize_numeral(2_147_483_647, NumeralStyle::Pekzep), "二十一億四七百四八万三六百四七");
+/// ```
+#[must_use]
+pub fn serialize_numeral(n: i64, style: NumeralStyle) -> String {
+    match style {
+        NumeralStyle::Arabic => n.to_string(),
+        NumeralStyle::Pekzep if n == 0 => String::from("無"),
+        NumeralStyle::Pekzep if n < 0 => format!("下{}", pekzep_positive(n.unsigned_abs())),
+        NumeralStyle::Pekzep => pekzep_positive(n.unsigned_abs()),
+    }
+}
+
+fn pekzep_digit(n: u64) -> char {
+    ['無', '一', '二', '三', '四', '五', '六', '七', '八', '九'][usize::try_from(n % 10).unwrap()]
+}
+
+fn pekzep_less_than_hundred(n: u64) -> String {
+    match (n / 10, n % 10) {
+        (0, d) => pekzep_digit(d).to_string(),
+        (1, 0) => String::from("十"),
+        (1, d) => format!("十{}", pekzep_digit(d)),
+        (t, 0) => format!("{}十", pekzep_digit(t)),
+        (t, d) => format!("{}十{}", pekzep_digit(t), pekzep_digit(d)),
+    }
+}
+
+/// A number below a hundred, with the middle `十` dropped if it would otherwise take three characters
+fn pekzep_elided(n: u64) -> String {
+    match (n / 10, n % 10) {
+        (2..=9, d @ 1..=9) => format!("{}{}", pekzep_digit(n / 10), pekzep_digit(d)),
+        _ => pekzep_less_than_hundred(n),
+    }
+}
+
+fn pekzep_less_than_ten_thousand(n: u64) -> String {
+    if n < 100 {
+        return pekzep_less_than_hundred(n);
+    }
+    let (q, r) = (n / 100, n % 100);
+    format!(
+        "{}百{}",
+        if q == 1 {
+            String::new()
+        } else {
+            pekzep_elided(q)
+        },
+        if r == 0 {
+            String::new()
+        } else {
+            pekzep_elided(r)
+        }
+    )
+}
+
+fn pekzep_less_than_hundred_million(n: u64) -> String {
+    if n < 1_0000 {
+        return pekzep_less_than_ten_thousand(n);
+    }
+    let (q, r) = (n / 1_0000, n % 1_0000);
+    format!(
+        "{}万{}",
+        if q == 1 {
+            String::new()
+        } else {
+            pekzep_less_than_ten_thousand(q)
+        },
+        if r == 0 {
+            String::new()
+        } else {
+            pekzep_less_than_ten_thousand(r)
+        }
+    )
+}
+
+fn pekzep_positive(n: u64) -> String {
+    if n < 1_0000_0000 {
+        return pekzep_less_than_hundred_million(n);
+    }
+    let (q, r) = (n / 1_0000_0000, n % 1_0000_0000);
+    format!(
+        "{}億{}",
+        if q == 1 {
+            String::new()
+        } else {
+            pekzep_positive(q)
+        },
+        if r == 0 {
+            String::new()
+        } else {
+            pekzep_less_than_hundred_million(r)
+        }
+    )
+}
+
+/// Encloses the string with `open` and `close`, using the smallest number of `#` needed.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::serialize::serialize_braced_string;
+/// assert_eq!(serialize_braced_string("硬皇力", '{', '}'), "{硬皇力}");
+/// assert_eq!(serialize_braced_string("硬皇}力", '{', '}'), "#{硬皇}力}#");
+/// assert_eq!(serialize_braced_string("a]#b", '[', ']'), "##[a]#b]##");
+/// ```
+#[must_use]
+pub fn serialize_braced_string(s: &str, open: char, close: char) -> String {
+    let mut hashes = String::new();
+    while s.contains(&format!("{close}{hashes}")) {
+        hashes.push('#');
+    }
+    format!("{hashes}{open}{s}{close}{hashes}")
+}
+
+fn serialize_prof_or_wildcard(prof: PossiblyUnknown<Profession>) -> &'static str {
+    prof.map_or("片", serialize_prof)
+}
+
+/// `None` for a value outside 0 to 5, which no stick can show.
+fn serialize_stick_size(size: PossiblyUnknown<i32>) -> Option<char> {
+    match size {
+        None => Some('或'),
+        Some(n @ 0..=5) => u64::try_from(n).ok().map(pekzep_digit),
+        Some(_) => None,
+    }
+}
+
+fn serialize_water_stick(size: PossiblyUnknown<i32>, successful: bool) -> Option<String> {
+    Some(format!(
+        "水{}{}",
+        serialize_stick_size(size)?,
+        if successful { "" } else { "此無" }
+    ))
+}
+
+fn serialize_bridge_stick(size: PossiblyUnknown<i32>) -> Option<String> {
+    Some(format!("橋{}", serialize_stick_size(size)?))
+}
+
+fn serialize_tam_sqbracket(coord: PossiblyUnknown<Coord>) -> String {
+    format!(
+        "[{}]",
+        coord.map_or_else(|| String::from("或"), serialize_coord)
+    )
+}
+
+/// Writes the move in notation ⓪, or returns `None` if a stick is outside 0 to 5.
+/// A move read by the parser is written back to the same text, except that `皇[或]` is shortened to `皇` where possible.
+/// A parachute of unknown colour is written as `或車CI`, which only notation ① allows.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::movement::{parse, Move};
+/// use cetkaik_kiaak::serialize::serialize_move;
+/// use cetkaik_core::absolute::{Coord, Row, Column};
+/// for s in &["XU兵XY無撃裁", "LY弓ZY水一此無", "ME弓MIMY橋或此無", "LO弓NOCO橋四水五", "赤車CI", "PAU皇[或]CAIMAU", "KE皇LI[KE]KA"] {
+///     assert_eq!(serialize_move(&parse(s).unwrap().1).as_deref(), Some(*s));
+/// }
+/// let m = Move::NoStepAndWaterStick {
+///     src: Coord(Row::Y, Column::L),
+///     prof: None,
+///     dest: Coord(Row::Y, Column::Z),
+///     water_stick_size: Some(12),
+///     water_stick_successful: true,
+/// };
+/// assert_eq!(serialize_move(&m), None);
+/// ```
+#[must_use]
+pub fn serialize_move(m: &Move) -> Option<String> {
+    if let Some((src, prof, step, dest, sticks)) = piece_move_parts(m) {
+        return Some(format!(
+            "{}{}{}{}{}",
+            serialize_coord(src),
+            serialize_prof_or_wildcard(prof),
+            step.map_or_else(String::new, serialize_coord),
+            serialize_coord(dest),
+            sticks
+        ));
+    }
+
+    Some(match *m {
+        Move::TamNoStep {
+            src,
+            first_dest,
+            second_dest,
+        } => format!(
+            "{}皇{}{}",
+            serialize_coord(src),
+            first_dest.map_or_else(String::new, |c| serialize_tam_sqbracket(Some(c))),
+            serialize_coord(second_dest)
+        ),
+        Move::TamStepUnspecified {
+            src,
+            step,
+            second_dest,
+        } => format!(
+            "{}皇{}{}",
+            serialize_coord(src),
+            serialize_coord(step),
+            serialize_coord(second_dest)
+        ),
+        Move::TamStepDuringFormer {
+            src,
+            step,
+            first_dest,
+            second_dest,
+        } => format!(
+            "{}皇{}{}{}",
+            serialize_coord(src),
+            serialize_coord(step),
+            serialize_tam_sqbracket(first_dest),
+            serialize_coord(second_dest)
+        ),
+        Move::TamStepDuringLatter {
+            src,
+            first_dest,
+            step,
+            second_dest,
+        } => format!(
+            "{}皇{}{}{}",
+            serialize_coord(src),
+            serialize_tam_sqbracket(first_dest),
+            serialize_coord(step),
+            serialize_coord(second_dest)
+        ),
+        Move::Parachute { color, prof, dest } => format!(
+            "{}{}{}",
+            color.map_or("或", serialize_color),
+            serialize_prof(prof),
+            serialize_coord(dest)
+        ),
+        /* a move of a non-Tam2 piece on the board that is not written above has a stick outside 0 to 5 */
+        _ => return None,
+    })
+}
+
+/// The source, the profession, the step, the destination and the sticks of a move of a non-Tam2 piece on the board.
+/// `None` for any other move, and for a move with a stick outside 0 to 5.
+type PieceMoveParts = (
+    Coord,
+    PossiblyUnknown<Profession>,
+    Option<Coord>,
+    Coord,
+    String,
+);
+
+fn piece_move_parts(m: &Move) -> Option<PieceMoveParts> {
+    match *m {
+        Move::NoStepAndNoStick { src, prof, dest } => {
+            Some((src, prof, None, dest, String::from("無撃裁")))
+        }
+        Move::NoStepAndWaterStick {
+            src,
+            prof,
+            dest,
+            water_stick_size,
+            water_stick_successful,
+        } => Some((
+            src,
+            prof,
+            None,
+            dest,
+            serialize_water_stick(water_stick_size, water_stick_successful)?,
+        )),
+        Move::StepAndNoStick {
+            src,
+            prof,
+            step,
+            dest,
+        } => Some((src, prof, Some(step), dest, String::from("無撃裁"))),
+        Move::StepAndWaterStick {
+            src,
+            prof,
+            step,
+            dest,
+            water_stick_size,
+            water_stick_successful,
+        } => Some((
+            src,
+            prof,
+            Some(step),
+            dest,
+            serialize_water_stick(water_stick_size, water_stick_successful)?,
+        )),
+        Move::StepAndBridgeStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size,
+            bridge_stick_successful,
+        } => Some((
+            src,
+            prof,
+            Some(step),
+            dest,
+            format!(
+                "{}{}",
+                serialize_bridge_stick(bridge_stick_size)?,
+                if bridge_stick_successful {
+                    ""
+                } else {
+                    "此無"
+                }
+            ),
+        )),
+        Move::StepAndBridgeStickAndWaterStick {
+            src,
+            prof,
+            step,
+            dest,
+            bridge_stick_size,
+            water_stick_size,
+            water_stick_successful,
+        } => Some((
+            src,
+            prof,
+            Some(step),
+            dest,
+            format!(
+                "{}{}",
+                serialize_bridge_stick(bridge_stick_size)?,
+                serialize_water_stick(water_stick_size, water_stick_successful)?
+            ),
+        )),
+        _ => None,
+    }
+}
+
+pub(crate) const fn serialize_season(season: Season) -> &'static str {
+    match season {
+        Season::Spring => "春",
+        Season::Summer => "夏",
+        Season::Fall => "秋",
+        Season::Winter => "冬",
+        Season::Up => "上季",
+        Season::Down => "下季",
+    }
+}
+
+/// The hands are sorted so that the output does not depend on the iteration order of the set.
+/// `None` if the player or the hands are unknown, since notation ⓪ has no way to leave them out.
+/// The points of the hands are left out, since notation ⓪ has no way to write them.
+fn serialize_hand_creation(hand_creation: &HandCreation) -> Option<String> {
+    let player_name = hand_creation.player_name.as_ref()?;
+    let mut hands: Vec<_> = hand_creation.hands.as_ref()?.iter().copied().collect();
+    hands.sort();
+    Some(format!(
+        "{}為{}",
+        serialize_braced_string(player_name, '[', ']'),
+        hands
+            .into_iter()
+            .map(|h| serialize_braced_string(h.kanji(), '(', ')'))
+            .collect::<String>()
+    ))
+}
+
+/// Writes a body element in notation ⓪, or returns `None` for a hand creation whose player or hands are unknown
+/// and for a move with a stick outside 0 to 5.
+/// A taxot whose points are unknown also returns `None`, since notation ⓪ requires `手` after `終季`.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation2;
+/// use cetkaik_kiaak::serialize::{serialize_body_elem, NumeralStyle};
+/// let (_, elem) = notation2::parse_body_elem("[SY] zau 獣 hop1 un1. ty mok1.").unwrap();
+/// assert_eq!(serialize_body_elem(&elem, NumeralStyle::Pekzep).as_deref(), Some("[SY]為(獣)再行"));
+/// let (_, elem) = notation2::parse_body_elem("[SY] zau 獣 hop1 un1. ta xot1.").unwrap();
+/// assert_eq!(serialize_body_elem(&elem, NumeralStyle::Pekzep), None);
+/// ```
+#[must_use]
+pub fn serialize_body_elem(elem: &Elem, style: NumeralStyle) -> Option<String> {
+    serialize_body_elem_with(elem, style, serialize_move)
+}
+
+/// Writes a body element in notation ⓪, except that the moves are written by `write_move`.
+fn serialize_body_elem_with(
+    elem: &Elem,
+    style: NumeralStyle,
+    write_move: fn(&Move) -> Option<String>,
+) -> Option<String> {
+    Some(match elem {
+        Elem::Move(m, None) => write_move(m)?,
+        Elem::Move(m, Some(comment)) => {
+            format!(
+                "{}{}",
+                write_move(m)?,
+                serialize_braced_string(comment, '{', '}')
+            )
+        }
+        Elem::CaptureComment(color, prof) => format!(
+            "手{}{}",
+            color.map_or("", serialize_color),
+            serialize_prof(*prof)
+        ),
+        Elem::TaXotTyMok(hand_creation, Action::TyMok) => {
+            format!("{}再行", serialize_hand_creation(hand_creation)?)
+        }
+        Elem::TaXotTyMok(hand_creation, Action::TaXot(Some(points))) => format!(
+            "{}終季 手{}",
+            serialize_hand_creation(hand_creation)?,
+            serialize_numeral(*points, style)
+        ),
+        Elem::TaXotTyMok(_, Action::TaXot(None)) => return None,
+        Elem::SeasonEnd(season) => format!("{}終", serialize_season(*season)),
+        Elem::GameEnd => String::from("星一周"),
+        Elem::Points(p1, p2) => serialize_points(p1, p2, style),
+    })
+}
+
+/// Writes the body in notation ⓪, one element per line. A capture comment stays on the line of the move it follows.
+/// The punctuation mark after each element, if any, is written back as it was.
+/// A hand creation whose player or hands are unknown, as read from notation ③, is an error.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::serialize::{serialize_body, NumeralStyle, UnwritableElem};
+/// let (_, body) = parse_body("XU兵XY無撃裁、XAI兵XY無撃裁、XU兵XY無撃裁。 手兵").unwrap();
+/// assert_eq!(
+///     serialize_body(&body, NumeralStyle::Pekzep),
+///     Ok(String::from("XU兵XY無撃裁、\nXAI兵XY無撃裁、\nXU兵XY無撃裁。 手兵\n"))
+/// );
+/// let (_, body) = parse_body("\"xiPxu\", \"xaiPxy\", \"xuPxy=tymor\"").unwrap();
+/// assert_eq!(serialize_body(&body, NumeralStyle::Pekzep), Err(UnwritableElem { index: 3 }));
+/// ```
+pub fn serialize_body(body: &Body, style: NumeralStyle) -> Result<String, UnwritableElem> {
+    serialize_body_with(body, style, serialize_body_elem)
+}
+
+fn serialize_body_with(
+    body: &Body,
+    style: NumeralStyle,
+    write_elem: fn(&Elem, NumeralStyle) -> Option<String>,
+) -> Result<String, UnwritableElem> {
+    let mut ans = String::new();
+    for (index, elem) in body.elems().enumerate() {
+        if let Elem::CaptureComment(..) = elem {
+            if ans.ends_with('\n') {
+                ans.pop();
+                ans.push(' ');
+            }
+        }
+        ans.push_str(&write_elem(elem, style).ok_or(UnwritableElem { index })?);
+        if let Some(punctuation) = body.punctuation_after(index) {
+            ans.push(punctuation.to_char());
+        }
+        ans.push('\n');
+    }
+    Ok(ans)
+}
+
+/// A line of the two-column layout
+enum Line {
+    /// The moves of the player who moves first in the season and of the other player, each with its capture comment
+    Row(Option<String>, Option<String>),
+
+    /// An element other than a move
+    Single(String),
+
+    Blank,
+}
+
+/// The number of columns the text takes in a monospace font, where a character outside ASCII takes two
+/// unless it is a halfwidth form such as `｡`.
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii() || ('\u{ff61}'..='\u{ff9f}').contains(&c) {
+                1
+            } else {
+                2
+            }
+        })
+        .sum()
+}
+
+/// Writes the body in notation ⓪ with the moves laid out in two columns, as in `sample1.txt`.
+/// The left column holds the moves of the player who moves first in the season, and the right column those of the other player.
+/// A capture comment follows its move, any other element takes a line of its own, and a taxot is followed by a blank line.
+/// As in `serialize_body`, a hand creation whose player or hands are unknown is an error.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::serialize::{serialize_body_in_columns, NumeralStyle};
+/// let (_, body) = parse_body("XI兵XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 手兵 [SY]為(獣)再行 ZAI船ZIZU無撃裁").unwrap();
+/// assert_eq!(
+///     serialize_body_in_columns(&body, NumeralStyle::Pekzep).unwrap(),
+///     "XI兵XU無撃裁      XAI兵XY無撃裁\nXU兵XY無撃裁 手兵\n[SY]為(獣)再行\n                  ZAI船ZIZU無撃裁\n"
+/// );
+/// assert_eq!(parse_body(&serialize_body_in_columns(&body, NumeralStyle::Pekzep).unwrap()), Ok(("", body)));
+/// ```
+pub fn serialize_body_in_columns(
+    body: &Body,
+    style: NumeralStyle,
+) -> Result<String, UnwritableElem> {
+    let mut lines = vec![];
+    let mut moves_in_season = 0;
+    for (index, elem) in body.elems().enumerate() {
+        let mut text = serialize_body_elem(elem, style).ok_or(UnwritableElem { index })?;
+        if let Some(punctuation) = body.punctuation_after(index) {
+            text.push(punctuation.to_char());
+        }
+        match elem {
+            Elem::Move(..) => {
+                let is_left = moves_in_season % 2 == 0;
+                moves_in_season += 1;
+                match lines.last_mut() {
+                    Some(Line::Row(Some(_), right @ None)) if !is_left => *right = Some(text),
+                    _ if is_left => lines.push(Line::Row(Some(text), None)),
+                    _ => lines.push(Line::Row(None, Some(text))),
+                }
+            }
+            Elem::CaptureComment(..) => match lines.last_mut() {
+                Some(Line::Row(_, Some(cell)) | Line::Row(Some(cell), None)) => {
+                    cell.push(' ');
+                    cell.push_str(&text);
+                }
+                _ => lines.push(Line::Single(text)),
+            },
+            Elem::TaXotTyMok(_, Action::TaXot(_)) => {
+                lines.push(Line::Single(text));
+                lines.push(Line::Blank);
+                moves_in_season = 0;
+            }
+            Elem::TaXotTyMok(..) | Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => {
+                lines.push(Line::Single(text));
+            }
+        }
+    }
+    while let Some(Line::Blank) = lines.last() {
+        lines.pop();
+    }
+
+    let width = lines
+        .iter()
+        .filter_map(|line| match line {
+            Line::Row(Some(left), _) => Some(display_width(left) + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    Ok(lines
+        .into_iter()
+        .map(|line| match line {
+            Line::Row(Some(left), Some(right)) => format!(
+                "{}{}{}\n",
+                left,
+                " ".repeat(width - display_width(&left)),
+                right
+            ),
+            Line::Row(None, Some(right)) => format!("{}{}\n", " ".repeat(width), right),
+            Line::Row(Some(cell), None) | Line::Single(cell) => format!("{cell}\n"),
+            Line::Row(None, None) | Line::Blank => String::from("\n"),
+        })
+        .collect())
+}
+
+/// The sticks of a move of a non-Tam2 piece on the board, each with its value and whether the piece got past it.
+/// A bridge stick followed by a water stick is always passed.
+fn sticks_of(m: &Move) -> Vec<(PossiblyUnknown<i32>, bool)> {
+    match *m {
+        Move::NoStepAndWaterStick {
+            water_stick_size,
+            water_stick_successful,
+            ..
+        }
+        | Move::StepAndWaterStick {
+            water_stick_size,
+            water_stick_successful,
+            ..
+        } => vec![(water_stick_size, water_stick_successful)],
+        Move::StepAndBridgeStick {
+            bridge_stick_size,
+            bridge_stick_successful,
+            ..
+        } => vec![(bridge_stick_size, bridge_stick_successful)],
+        Move::StepAndBridgeStickAndWaterStick {
+            bridge_stick_size,
+            water_stick_size,
+            water_stick_successful,
+            ..
+        } => vec![
+            (bridge_stick_size, true),
+            (water_stick_size, water_stick_successful),
+        ],
+        _ => vec![],
+    }
+}
+
+/// Writes the move in notation ①, leaving out `無撃裁`, `橋` and `水` as long as the move is read back the same.
+/// A move that notation ① cannot shorten, such as a move of Tam2, is written as in notation ⓪, which notation ① also accepts.
+/// Returns `None` if a stick is outside 0 to 5.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::{movement, notation1};
+/// use cetkaik_kiaak::serialize::serialize_move_in_notation1;
+/// for (zero, one) in &[
+///     ("XU兵XY無撃裁", "XU兵XY"),
+///     ("ME弓MIMU橋四", "ME弓MIMU四"),
+///     ("ME弓MIMY橋或此無", "ME弓MIMY無"),
+///     ("LY弓ZY水一此無", "LY弓ZY一"),
+///     ("LO弓NOCO橋四水五", "LO弓NOCO四五"),
+///     ("KE皇LI[KE]KA", "KE皇LI[KE]KA"),
+/// ] {
+///     let m = movement::parse(zero).unwrap().1;
+///     assert_eq!(serialize_move_in_notation1(&m).as_deref(), Some(*one));
+///     assert_eq!(notation1::parse(one), Ok(("", m)));
+/// }
+/// ```
+#[must_use]
+pub fn serialize_move_in_notation1(m: &Move) -> Option<String> {
+    let Some((src, prof, step, dest, _)) = piece_move_parts(m) else {
+        return serialize_move(m);
+    };
+
+    let sticks = sticks_of(m);
+    let mut values = String::new();
+    for (i, (size, successful)) in sticks.iter().enumerate() {
+        match size {
+            /* `無` alone would be a failure of unknown value */
+            Some(0) => values.push_str("無此無"),
+            Some(_) => values.push(serialize_stick_size(*size)?),
+            None if !successful => values.push('無'),
+            None if i + 1 == sticks.len() => {}
+            None => values.push('或'),
+        }
+    }
+
+    let shortest = format!(
+        "{}{}{}{}{}",
+        serialize_coord(src),
+        serialize_prof_or_wildcard(prof),
+        step.map_or_else(String::new, serialize_coord),
+        serialize_coord(dest),
+        values
+    );
+    let with_failure = format!("{shortest}此無");
+    [shortest, with_failure]
+        .iter()
+        .find(|text| notation1::parse(text) == Ok(("", m.clone())))
+        .cloned()
+        .or_else(|| serialize_move(m))
+}
+
+/// Writes a body element in notation ①, or returns `None` if notation ① cannot write it.
+/// A hand creation whose player and hands are both unknown is left out, as in `再行` or `終季 手二十`,
+/// and the points of the hands are written after the hand creation, as in `[SY]為(獣)而手五 終季`.
+/// A taxot whose points are unknown needs them, so they are worked out from the hands if the record leaves them out.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::notation1;
+/// use cetkaik_kiaak::serialize::{serialize_body_elem_in_notation1, NumeralStyle};
+/// for text in &["再行", "終季 手二十", "[SY]為(獣)而手五 終季", "[SY]為(獣)而手三 再行", "[SY]為(獣)再行"] {
+///     let (_, (elem, _)) = notation1::parse_body_elem_with_punctuation(text).unwrap();
+///     assert_eq!(serialize_body_elem_in_notation1(&elem, NumeralStyle::Pekzep).as_deref(), Some(*text));
+/// }
+/// ```
+#[must_use]
+pub fn serialize_body_elem_in_notation1(elem: &Elem, style: NumeralStyle) -> Option<String> {
+    let Elem::TaXotTyMok(hand_creation, action) = elem else {
+        return serialize_body_elem_with(elem, style, serialize_move_in_notation1);
+    };
+    let action_text = match action {
+        Action::TyMok => String::from("再行"),
+        Action::TaXot(None) => String::from("終季"),
+        Action::TaXot(Some(points)) => format!("終季 手{}", serialize_numeral(*points, style)),
+    };
+    /* `而手` is required before a bare `終季`, so the points of the hands are worked out if the record leaves them out */
+    let points = match (&hand_creation.hands, hand_creation.points, action) {
+        (_, Some(points), _) => Some(points),
+        (Some(hands), None, Action::TaXot(None)) => {
+            Some(hands.iter().map(|hand| hand.points()).sum())
+        }
+        _ => None,
+    };
+    match (&hand_creation.player_name, &hand_creation.hands, points) {
+        (None, None, _) => Some(action_text),
+        (Some(_), Some(_), Some(points)) => Some(format!(
+            "{}而手{} {}",
+            serialize_hand_creation(hand_creation)?,
+            serialize_numeral(points, style),
+            action_text
+        )),
+        _ => serialize_body_elem_with(elem, style, serialize_move),
+    }
+}
+
+/// Writes the body in notation ①, one element per line, in the same way as `serialize_body`.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::serialize::{serialize_body_in_notation1, NumeralStyle};
+/// let (_, body) = parse_body("XI兵XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 手兵").unwrap();
+/// let text = serialize_body_in_notation1(&body, NumeralStyle::Pekzep).unwrap();
+/// assert_eq!(text, "XI兵XU\nXAI兵XY\nXU兵XY 手兵\n");
+/// assert_eq!(parse_body(&text), Ok(("", body)));
+/// ```
+pub fn serialize_body_in_notation1(
+    body: &Body,
+    style: NumeralStyle,
+) -> Result<String, UnwritableElem> {
+    serialize_body_with(body, style, serialize_body_elem_in_notation1)
+}
+
+/// A body element that cannot be written in the notation asked for, given by its index in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct UnwritableElem {
+    pub index: usize,
+}
+
+const fn pekzep_prof(prof: Profession) -> &'static str {
+    match prof {
+        Profession::Nuak1 => "nuak1",
+        Profession::Kauk2 => "kauk2",
+        Profession::Gua2 => "gua2",
+        Profession::Kaun1 => "kaun1",
+        Profession::Dau2 => "dau2",
+        Profession::Maun1 => "maun1",
+        Profession::Kua2 => "kua2",
+        Profession::Tuk2 => "tuk2",
+        Profession::Uai1 => "uai1",
+        Profession::Io => "io",
+    }
+}
+
+const fn pekzep_color(color: Color) -> &'static str {
+    match color {
+        Color::Kok1 => "kok1",
+        Color::Huok2 => "huok2",
+    }
+}
+
+/// The number written as space-separated Pekzep syllables, or `None` if it needs `億`, which has no syllable in notation ②.
+fn pekzep_syllables(n: i64) -> Option<String> {
+    let syllables = serialize_numeral(n, NumeralStyle::Pekzep)
+        .chars()
+        .map(|c| match c {
+            '無' => Some("mun1"),
+            '下' => Some("ut2"),
+            '一' => Some("et2"),
+            '二' => Some("ik2"),
+            '三' => Some("om2"),
+            '四' => Some("ap1"),
+            '五' => Some("un1"),
+            '六' => Some("net2"),
+            '七' => Some("nik2"),
+            '八' => Some("nom2"),
+            '九' => Some("nap1"),
+            '十' => Some("nun1"),
+            '百' => Some("kit1"),
+            '万' => Some("ue1"),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(syllables.join(" "))
+}
+
+/// Writes the move in notation ②, or returns `None` if notation ② cannot tell it from another move.
+/// This is the case for a move of an unknown profession, a parachute of an unknown colour,
+/// a Tam2 whose first destination is given, and a move whose sticks cannot be written with the syllables.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::{movement, notation2};
+/// use cetkaik_kiaak::serialize::serialize_move_in_notation2;
+/// for (zero, two) in &[
+///     ("TU虎XY無撃裁", "TU dau2 XY"),
+///     ("LE弓TEZE橋四", "LE gua2 TE ZE ap1"),
+///     ("LE弓TEZE橋四此無", "LE gua2 TE ZE ap1 LE"),
+///     ("ZO皇ZUXI", "ZO tam2 ZU XI"),
+///     ("黒車CI", "huok2 kaun1 CI"),
+/// ] {
+///     let m = movement::parse(zero).unwrap().1;
+///     assert_eq!(serialize_move_in_notation2(&m).as_deref(), Some(*two));
+///     assert_eq!(notation2::parse(two), Ok(("", m)));
+/// }
+/// let m = movement::parse("XU片XY無撃裁").unwrap().1;
+/// assert_eq!(serialize_move_in_notation2(&m), None);
+/// ```
+#[must_use]
+pub fn serialize_move_in_notation2(m: &Move) -> Option<String> {
+    let candidates = match *m {
+        Move::Parachute { color, prof, dest } => vec![format!(
+            "{} {} {}",
+            pekzep_color(color?),
+            pekzep_prof(prof),
+            serialize_coord(dest)
+        )],
+        Move::TamNoStep {
+            src, second_dest, ..
+        } => vec![format!(
+            "{} tam2 {}",
+            serialize_coord(src),
+            serialize_coord(second_dest)
+        )],
+        Move::TamStepUnspecified {
+            src,
+            step,
+            second_dest,
+        } => vec![format!(
+            "{} tam2 {} {}",
+            serialize_coord(src),
+            serialize_coord(step),
+            serialize_coord(second_dest)
+        )],
+        _ => {
+            let (src, prof, step, dest, _) = piece_move_parts(m)?;
+            let mut words = vec![serialize_coord(src), String::from(pekzep_prof(prof?))];
+            words.extend(step.map(serialize_coord));
+            words.push(serialize_coord(dest));
+            for (size, successful) in sticks_of(m) {
+                match size {
+                    Some(n @ 1..=5) => words.push(pekzep_syllables(n.into())?),
+                    Some(_) => return None,
+                    None if !successful => words.push(String::from("mun1")),
+                    None => {}
+                }
+            }
+            let shortest = words.join(" ");
+            let with_failure = format!("{} {}", shortest, serialize_coord(src));
+            vec![shortest, with_failure]
+        }
+    };
+    candidates
+        .into_iter()
+        .find(|text| notation2::parse(text) == Ok(("", m.clone())))
+}
+
+/// Writes the body in notation ②, one element per line, each followed by `.`.
+/// Notation ② has no way to write capture comments, the ends of seasons and of the game, points, or comments on moves,
+/// so these are left out, and so are the points that a taxot pays out. Any other element that notation ② cannot write is an error,
+/// including a hand creation whose player or hands are unknown.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::serialize::{serialize_body_in_notation2, UnwritableElem};
+/// let (_, body) = parse_body("XI兵XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 手兵 [SY]為(獣)終季 手五").unwrap();
+/// assert_eq!(
+///     serialize_body_in_notation2(&body),
+///     Ok(String::from("XI kauk2 XU.\nXAI kauk2 XY.\nXU kauk2 XY.\n[SY] zau 獣.\nta xot1.\n"))
+/// );
+/// let (_, body) = parse_body("XI兵XU XAI兵XY XU兵XY [SY]為獣而手五 終季").unwrap();
+/// assert_eq!(
+///     serialize_body_in_notation2(&body),
+///     Ok(String::from("XI kauk2 XU.\nXAI kauk2 XY.\nXU kauk2 XY.\n[SY] zau 獣 hop1 un1.\nta xot1.\n"))
+/// );
+/// let (_, body) = parse_body("XI兵XU無撃裁 XAI片XY無撃裁").unwrap();
+/// assert_eq!(serialize_body_in_notation2(&body), Err(UnwritableElem { index: 1 }));
+/// ```
+pub fn serialize_body_in_notation2(body: &Body) -> Result<String, UnwritableElem> {
+    let mut ans = String::new();
+    for (index, elem) in body.elems().enumerate() {
+        match elem {
+            Elem::Move(m, _) => {
+                ans.push_str(&serialize_move_in_notation2(m).ok_or(UnwritableElem { index })?);
+                ans.push_str(".\n");
+            }
+            Elem::TaXotTyMok(hand_creation, action) => {
+                let (Some(player_name), Some(hands)) =
+                    (&hand_creation.player_name, &hand_creation.hands)
+                else {
+                    return Err(UnwritableElem { index });
+                };
+                let mut hands: Vec<_> = hands.iter().copied().collect();
+                hands.sort();
+                let mut words = vec![
+                    serialize_braced_string(player_name, '[', ']'),
+                    String::from("zau"),
+                ];
+                words.extend(hands.into_iter().map(|hand| {
+                    String::from(if hand == Hand::King {
+                        "io"
+                    } else {
+                        hand.kanji()
+                    })
+                }));
+                if let Some(points) = hand_creation.points {
+                    words.push(String::from("hop1"));
+                    words.push(pekzep_syllables(points).ok_or(UnwritableElem { index })?);
+                }
+                /* what a taxot pays out has no place in notation ②, and follows from the hands anyway */
+                let action_word = match action {
+                    Action::TaXot(_) => "ta xot1",
+                    Action::TyMok => "ty mok1",
+                };
+                ans.push_str(&words.join(" "));
+                ans.push_str(".\n");
+                ans.push_str(action_word);
+                ans.push_str(".\n");
+            }
+            Elem::CaptureComment(..) | Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => {}
+        }
+    }
+    Ok(ans)
+}
+
+const fn mrf_piece(prof: Profession) -> char {
+    match prof {
+        Profession::Nuak1 => 'V',
+        Profession::Kauk2 => 'P',
+        Profession::Gua2 => 'A',
+        Profession::Kaun1 => 'C',
+        Profession::Dau2 => 'T',
+        Profession::Maun1 => 'H',
+        Profession::Kua2 => 'O',
+        Profession::Tuk2 => 'S',
+        Profession::Uai1 => 'F',
+        Profession::Io => 'K',
+    }
+}
+
+fn mrf_square(coord: Coord) -> String {
+    serialize_coord(coord).to_lowercase()
+}
+
+/// Writes the content of the MRF string for the move, without the quotes, or returns `None` if notation ③ cannot tell it from another move.
+/// This is the case for a move of an unknown profession, a parachute of an unknown colour,
+/// and a move whose sticks cannot be written with the digits.
+/// MRF does not record where Tam2 stops before its second move, so the first destination of Tam2 is left out.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::body::{movement, notation3};
+/// use cetkaik_kiaak::serialize::serialize_move_in_notation3;
+/// for (zero, three) in &[
+///     ("MAU弓MAIMY橋一", "mauAmaimy1"),
+///     ("ME弓XEZE橋三", "meAxeze3"),
+///     ("ZO皇NINE", "zoMnine"),
+///     ("黒船ZI", "BVzi"),
+/// ] {
+///     let m = movement::parse(zero).unwrap().1;
+///     assert_eq!(serialize_move_in_notation3(&m).as_deref(), Some(*three));
+///     assert_eq!(notation3::parse_movement(three), Ok(("", m)));
+/// }
+/// let m = movement::parse("ZO皇[ZU]ZY").unwrap().1;
+/// assert_eq!(serialize_move_in_notation3(&m).as_deref(), Some("zoMzy"));
+/// let m = movement::parse("XU片XY無撃裁").unwrap().1;
+/// assert_eq!(serialize_move_in_notation3(&m), None);
+/// ```
+#[must_use]
+pub fn serialize_move_in_notation3(m: &Move) -> Option<String> {
+    let (text, expected) = match *m {
+        Move::Parachute { color, prof, dest } => (
+            format!(
+                "{}{}{}",
+                if color? == Color::Kok1 { 'R' } else { 'B' },
+                mrf_piece(prof),
+                mrf_square(dest)
+            ),
+            m.clone(),
+        ),
+        Move::TamNoStep {
+            src, second_dest, ..
+        } => (
+            format!("{}M{}", mrf_square(src), mrf_square(second_dest)),
+            Move::TamNoStep {
+                src,
+                first_dest: None,
+                second_dest,
+            },
+        ),
+        Move::TamStepUnspecified {
+            src,
+            step,
+            second_dest,
+        }
+        | Move::TamStepDuringFormer {
+            src,
+            step,
+            second_dest,
+            ..
+        }
+        | Move::TamStepDuringLatter {
+            src,
+            step,
+            second_dest,
+            ..
+        } => (
+            format!(
+                "{}M{}{}",
+                mrf_square(src),
+                mrf_square(step),
+                mrf_square(second_dest)
+            ),
+            Move::TamStepUnspecified {
+                src,
+                step,
+                second_dest,
+            },
+        ),
+        _ => {
+            let (src, prof, step, dest, _) = piece_move_parts(m)?;
+            let mut text = format!("{}{}", mrf_square(src), mrf_piece(prof?));
+            text.extend(step.map(mrf_square));
+            text.push_str(&mrf_square(dest));
+            for (size, _) in sticks_of(m) {
+                match size {
+                    Some(n @ 0..=5) => text.push_str(&n.to_string()),
+                    Some(_) => return None,
+                    None => {}
+                }
+            }
+            (text, m.clone())
+        }
+    };
+    (notation3::parse_movement(&text) == Ok(("", expected))).then_some(text)
+}
+
+/// The `=tymor` or `=taxt=` annotation for the hand creation, or `None` if a declaration of ta xot names no hand or leaves the hands unknown.
+/// The hands are written from the one worth the most, or made of the most pieces, and the pieces of each hand from the highest profession down, as in `FHCV/HT`.
+/// A colour-matched hand is marked by a further `F` in front, as in `FHT` for 同色獣.
+fn mrf_hand_annotation(hand_creation: &HandCreation, action: &Action) -> Option<String> {
+    match action {
+        Action::TyMok => Some(String::from("=tymor")),
+        Action::TaXot(_) => {
+            let hands = hand_creation
+                .hands
+                .as_ref()
+                .filter(|hands| !hands.is_empty())?;
+            let mut hands: Vec<_> = hands.iter().copied().collect();
+            hands.sort_by_key(|hand| {
+                (
+                    std::cmp::Reverse(hand.points()),
+                    std::cmp::Reverse(hand.pieces().len()),
+                    *hand,
+                )
+            });
+            let hands: Vec<String> = hands
+                .into_iter()
+                .map(|hand| {
+                    let mut pieces = hand.pieces().to_vec();
+                    pieces.sort_by_key(|prof| std::cmp::Reverse(*prof as u8));
+                    let mut text = String::from(if hand.is_same_color() { "F" } else { "" });
+                    text.extend(pieces.into_iter().map(mrf_piece));
+                    text
+                })
+                .collect();
+            Some(format!("=taxt={}", hands.join("/")))
+        }
+    }
+}
+
+/// Writes the body in notation ③, one string literal per line, separated by `,`.
+/// See [`serialize_body_in_notation3_with`].
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::serialize::serialize_body_in_notation3;
+/// let (_, body) = parse_body("XI片XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 MAI兵MY無撃裁 或兵XU").unwrap();
+/// assert_eq!(
+///     serialize_body_in_notation3(&body),
+///     Ok(String::from("\"xiPxu\",\n\"xaiPxy\",\n\"xuPxy\",\n\"maiPmy\",\n\"RPxu\"\n"))
+/// );
+/// ```
+pub fn serialize_body_in_notation3(body: &Body) -> Result<String, UnwritableElem> {
+    serialize_body_in_notation3_with(body, Config::standard())
+}
+
+/// Writes the body in notation ③ under the given rules.
+///
+/// MRF always names the piece that moves and the colour of the piece that is dropped,
+/// so the body is replayed to fill in what the record leaves unknown.
+/// A hand creation is written as an annotation on the move before it; MRF records neither the player nor the points,
+/// so these are left out, together with the hands of a declaration of ty mok.
+/// Capture comments, the ends of seasons and of the game, points, and comments on moves are also left out.
+/// Any other element that notation ③ cannot write is an error, as is a hand creation that does not follow a move.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_body;
+/// use cetkaik_kiaak::rule::Config;
+/// use cetkaik_kiaak::serialize::{serialize_body_in_notation3_with, UnwritableElem};
+/// let (_, body) = parse_body("XI兵XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 手赤兵 [SY]為(同色獣)(王)終季 手五").unwrap();
+/// assert_eq!(
+///     serialize_body_in_notation3_with(&body, Config::standard()),
+///     Ok(String::from("\"xiPxu\",\n\"xaiPxy\",\n\"xuPxy=taxt=FHT/K\"\n"))
+/// );
+/// let (_, body) = parse_body("[SY]為(獣)再行").unwrap();
+/// assert_eq!(
+///     serialize_body_in_notation3_with(&body, Config::standard()),
+///     Err(UnwritableElem { index: 0 })
+/// );
+/// ```
+pub fn serialize_body_in_notation3_with(
+    body: &Body,
+    config: Config,
+) -> Result<String, UnwritableElem> {
+    /* once a move cannot be replayed, the rest of the moves are written as they are */
+    let mut state = Some(State::initial());
+    let mut strings: Vec<String> = vec![];
+    let mut last_is_move = false;
+    for (index, elem) in body.elems().enumerate() {
+        match elem {
+            Elem::Move(m, _) => {
+                let resolved = state
+                    .as_ref()
+                    .and_then(|state| state.resolve_move(m).ok())
+                    .unwrap_or_else(|| m.clone());
+                strings
+                    .push(serialize_move_in_notation3(&resolved).ok_or(UnwritableElem { index })?);
+                last_is_move = true;
+            }
+            Elem::TaXotTyMok(hand_creation, action) => {
+                let annotation = mrf_hand_annotation(hand_creation, action)
+                    .filter(|_| last_is_move)
+                    .ok_or(UnwritableElem { index })?;
+                strings
+                    .last_mut()
+                    .ok_or(UnwritableElem { index })?
+                    .push_str(&annotation);
+                last_is_move = false;
+            }
+            Elem::CaptureComment(..) => {}
+            Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => last_is_move = false,
+        }
+        state = state.and_then(|state| state.apply_elem_with(elem, config).ok());
+    }
+    Ok(strings
+        .iter()
+        .map(|content| format!("\"{content}\""))
+        .collect::<Vec<_>>()
+        .join(",\n")
+        + if strings.is_empty() { "" } else { "\n" })
+}
+
+fn serialize_header_elem(elem: &header::Elem) -> String {
+    match elem {
+        /* a value containing `:` would otherwise be read as a key */
+        header::Elem::Value(value) if value.contains(':') => {
+            serialize_braced_string(&format!(":{value}"), '{', '}')
+        }
+        header::Elem::Value(value) => serialize_braced_string(value, '{', '}'),
+        header::Elem::KeyedValue(key, value) => {
+            serialize_braced_string(&format!("{key}:{value}"), '{', '}')
+        }
+    }
+}
+
+fn serialize_points(p1: &PlayerAndPoint, p2: &PlayerAndPoint, style: NumeralStyle) -> String {
+    format!(
+        "{}{} {}{}",
+        serialize_braced_string(&p1.player_name, '[', ']'),
+        serialize_numeral(p1.point, style),
+        serialize_braced_string(&p2.player_name, '[', ']'),
+        serialize_numeral(p2.point, style)
+    )
+}
+
+/// Writes the header, one element per line. The points of both players are always written out.
+#[must_use]
+pub fn serialize_header(header: &Header, style: NumeralStyle) -> String {
+    let mut lines: Vec<String> = header.info.iter().map(serialize_header_elem).collect();
+    if let Some((p1, p2)) = &header.players {
+        lines.push(serialize_points(p1, p2, style));
+    }
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Writes the whole record in canonical notation ⓪, which `parse_ckka` reads back to the same `(Header, Body)`.
+/// Since the body is told apart from the header by its first character, the body must begin with a move that is not a parachute.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::serialize::{serialize_ckka, NumeralStyle};
+/// let (header, body) = parse_ckka("{律:硬皇力}\n[SY]十二 [JV]\nXU兵XY無撃裁{様子見}\n").unwrap();
+/// let text = serialize_ckka(&header, &body, NumeralStyle::Pekzep).unwrap();
+/// assert_eq!(text, "{律:硬皇力}\n[SY]十二 [JV]二十八\nXU兵XY無撃裁{様子見}\n");
+/// assert_eq!(parse_ckka(&text).unwrap(), (header, body));
+/// ```
+pub fn serialize_ckka(
+    header: &Header,
+    body: &Body,
+    style: NumeralStyle,
+) -> Result<String, UnwritableElem> {
+    Ok(format!(
+        "{}{}",
+        serialize_header(header, style),
+        serialize_body(body, style)?
+    ))
+}
+
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serialize_header(self, NumeralStyle::Pekzep))
+    }
+}
@@ -22,3 +22,1414 @@ fn sample1() {
     )
     .unwrap();
 }
+
+#[test]
+fn move_comments() {
+    use super::*;
+    use big_s::S;
+    use body::Elem;
+    let (_, body) = parse_ckka(
+        r"
+    [SY] [ぶちょー]
+    LAU弓LAILY橋或此無{定弓失敗} LE弓LILU橋二{定弓}
+    LAU弓LAILY橋或此無#{もう一度{失敗}}# ME弓XEZE無撃裁{王の守り}
+    NI兵NE無撃裁
+    ",
+    )
+    .unwrap();
+    let comments: Vec<_> = body
+        .elems()
+        .map(|e| match e {
+            Elem::Move(_, comment) => comment.clone(),
+            _ => panic!("unexpected element {:?}", e),
+        })
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            Some(S("定弓失敗")),
+            Some(S("定弓")),
+            Some(S("もう一度{失敗}")),
+            Some(S("王の守り")),
+            None
+        ]
+    );
+}
+
+#[test]
+fn notation1_is_detected() {
+    use super::*;
+    let body = r"
+LAU弓LAILY無{定弓失敗} LE弓LILU{定弓}
+LAU弓LAILY無{もう一度失敗} ME弓XEZE{王の守り}
+NAI兵LAILY{抗弓①} NI兵NE{皇処之巫狙い}
+LAU弓NAU{抗弓②} ZO皇NU{此善}
+";
+    let (rest, (notation, Body(elems))) = parse_body_and_detect_notation(body).unwrap();
+    assert_eq!(rest, "");
+    assert_eq!(notation, Notation::One);
+    assert_eq!(elems.len(), 8);
+
+    let (_, (notation, _)) = parse_body_and_detect_notation("XU兵XY無撃裁 LY弓ZY水或此無").unwrap();
+    assert_eq!(notation, Notation::Zero);
+}
+
+/// The code block under each `### 例` heading of the README, in order.
+fn readme_examples() -> Vec<&'static str> {
+    include_str!("../../README.md")
+        .split("\n### 例")
+        .skip(1)
+        .map(|section| {
+            let start = section.find("```\n").unwrap() + "```\n".len();
+            let end = start + section[start..].find("```").unwrap();
+            &section[start..end]
+        })
+        .collect()
+}
+
+#[test]
+fn readme_examples_parse() {
+    use super::*;
+    use body::hand::Hand;
+    use body::{Action, Elem, HandCreation};
+    let examples = readme_examples();
+    assert_eq!(examples.len(), 6);
+    let notations = [
+        Notation::One,
+        Notation::One,
+        Notation::One,
+        Notation::Two,
+        Notation::One,
+        Notation::Three,
+    ];
+    for (example, expected) in examples.iter().zip(notations.iter()) {
+        assert!(parse_ckka(example).is_ok(), "{}", example);
+        let (_, body) = split_header_and_body(example);
+        let (rest, (notation, _)) = parse_body_and_detect_notation(&body).unwrap();
+        assert_eq!((rest, notation), ("", *expected), "{example}");
+    }
+    for example in &[examples[1], examples[2], examples[4]] {
+        let (_, body) = parse_ckka(example).unwrap();
+        let text =
+            serialize::serialize_body_in_notation1(&body, serialize::NumeralStyle::Pekzep).unwrap();
+        assert_eq!(parse_body(&text), Ok(("", body)), "{text}");
+    }
+
+    let unknown = HandCreation {
+        player_name: None,
+        hands: None,
+        points: None,
+    };
+    let (_, body) = parse_ckka(examples[1]).unwrap();
+    assert_eq!(
+        body.0[13].0,
+        Elem::TaXotTyMok(unknown.clone(), Action::TyMok)
+    );
+    assert_eq!(
+        body.0.last().unwrap().0,
+        Elem::TaXotTyMok(unknown, Action::TaXot(Some(20)))
+    );
+
+    /* the points written after 而 are those of the hands, which differ from the standard ones in the README */
+    let declared = |hand, points| HandCreation {
+        player_name: Some(String::from("SY")),
+        hands: Some(std::iter::once(hand).collect()),
+        points: Some(points),
+    };
+    let (_, body) = parse_ckka(examples[2]).unwrap();
+    assert_eq!(
+        body.0.last().unwrap().0,
+        Elem::TaXotTyMok(declared(Hand::Beasts, 3), Action::TaXot(None))
+    );
+
+    let (_, body) = parse_ckka(examples[4]).unwrap();
+    let declarations: Vec<_> = body
+        .elems()
+        .filter(|elem| matches!(elem, Elem::TaXotTyMok(..)))
+        .cloned()
+        .collect();
+    assert_eq!(
+        declarations,
+        vec![
+            Elem::TaXotTyMok(declared(Hand::Beasts, 3), Action::TyMok),
+            Elem::TaXotTyMok(declared(Hand::Marching, 5), Action::TaXot(None))
+        ]
+    );
+    assert!(matches!(
+        body.elems().rev().take(2).collect::<Vec<_>>().as_slice(),
+        [Elem::Points(..), Elem::SeasonEnd(body::Season::Spring)]
+    ));
+}
+
+#[test]
+fn notation2_example() {
+    use super::*;
+    use body::{Action, Elem};
+    let (header, Body(elems)) = parse_ckka(
+        r"{律:硬皇力}
+{:2018年4月8日 17:30頃}
+[補集合] [hsjoihs]
+TAI kauk2 TY. XI kauk2 XU.
+XAI kauk2 XY. ZI nuak1 ZU.
+ZAI nuak1 ZY. ZO tam2 ZU XI.
+XAU dau2 ZAI. XA uai1 XE ZI.
+MAU gua2 ZAU. LE gua2 TE ZE ap1.
+XIA uai1 XAU. ZA io TE NE.
+PAU tuk2 PAI PY. XE dau2 ZI TU.
+NAI kauk2 NY. TA uai1 ZE XE.
+CAI kauk2 CY. XU kauk2 XY.
+ZAI dau2 XY. TU dau2 XY.
+TAU dau2 ZAI mun1. ME gua2 MI MU.
+MAI kauk2 MY. XY dau2 PIA.
+TAU dau2 NAI. ZU nuak1 ZY.
+ZAU gua2 ZY. MU gua2 MY MIA.
+CIA kaun1 XAU ZAI et2. MIA gua2 ZIA.
+[hsjoihs] zau io hop1 om2.
+ta xot1.
+",
+    )
+    .unwrap();
+    assert_eq!(header.players.unwrap().1.player_name, "hsjoihs");
+    assert_eq!(elems.len(), 31);
+    match &elems.last().unwrap().0 {
+        Elem::TaXotTyMok(hand_creation, Action::TaXot(None)) => {
+            assert_eq!(hand_creation.player_name.as_deref(), Some("hsjoihs"));
+            assert_eq!(hand_creation.points, Some(3));
+        }
+        e => panic!("unexpected element {:?}", e),
+    }
+
+    /* ZU holds the vessel moved there just before, so the Tam2 steps on it rather than stopping there */
+    assert_eq!(
+        elems[5].0,
+        Elem::Move(body::movement::parse("ZO皇ZUXI").unwrap().1, None)
+    );
+    assert!(replay::replay(&Body(elems)).all(|state| state.is_ok()));
+}
+
+#[test]
+fn notation3_example() {
+    use super::*;
+    use body::{Action, Elem, HandCreation};
+    use serialize::{
+        serialize_body, serialize_body_in_notation1, serialize_body_in_notation2, NumeralStyle,
+        UnwritableElem,
+    };
+    let (header, Body(elems)) = parse_ckka(
+        r#"{黒名:kuai1} {赤名:iei2}
+{一位色:赤} {季:夏}
+"meAxeze3",
+"mauAmaimy1",
+"zoMnine",
+"ziaKxiacau",
+"caCcizo4",
+"zaiVzo",
+"ziVzo"
+"caiPcy",
+"BVzi",
+"myAmima2",
+"paOma",
+"pauScaucai",
+"zoVto",
+"miaHxaito4=tymor",
+"tiPto3",
+"caiScixe1=taxt=FHCV/HT"
+"#,
+    )
+    .unwrap();
+    assert_eq!(header.info.len(), 4);
+    assert_eq!(header.players, None);
+    assert_eq!(elems.len(), 18);
+    assert_eq!(
+        elems[14].0,
+        Elem::TaXotTyMok(
+            HandCreation {
+                player_name: None,
+                hands: None,
+                points: None
+            },
+            Action::TyMok
+        )
+    );
+    assert!(matches!(
+        elems[17].0,
+        Elem::TaXotTyMok(
+            HandCreation {
+                player_name: None,
+                hands: Some(_),
+                points: None
+            },
+            Action::TaXot(None)
+        )
+    ));
+
+    /* NI holds a pawn, so the Tam2 steps on it rather than stopping there */
+    assert_eq!(
+        elems[2].0,
+        Elem::Move(body::movement::parse("ZO皇NINE").unwrap().1, None)
+    );
+    assert!(replay::replay(&Body(elems.clone())).all(|state| state.is_ok()));
+
+    /* `=tymor` names neither the player nor the hands, which only notation ① can leave out, as `再行`;
+    no notation but ③ can name the hands of `=taxt=` without naming the player */
+    let body = Body(elems);
+    let unwritable = Err(UnwritableElem { index: 14 });
+    assert_eq!(serialize_body(&body, NumeralStyle::Pekzep), unwritable);
+    assert_eq!(serialize_body_in_notation2(&body), unwritable);
+    assert_eq!(
+        serialize_body_in_notation1(&body, NumeralStyle::Pekzep),
+        Err(UnwritableElem { index: 17 })
+    );
+}
+
+#[test]
+fn notation3_round_trip() {
+    use super::*;
+    use serialize::serialize_body_in_notation3;
+    let strings = r#""meAxeze3",
+"mauAmaimy1",
+"zoMnine",
+"ziaKxiacau",
+"caCcizo4",
+"zaiVzo",
+"ziVzo",
+"caiPcy",
+"BVzi",
+"myAmima2",
+"paOma",
+"pauScaucai",
+"zoVto",
+"miaHxaito4=tymor",
+"tiPto3",
+"caiScixe1=taxt=FHCV/HT"
+"#;
+    let (_, body) = parse_ckka(&format!("{{一位色:赤}}\n{strings}")).unwrap();
+    assert_eq!(serialize_body_in_notation3(&body).as_deref(), Ok(strings));
+
+    /* the profession and the colour left unknown are filled in by replaying the record */
+    let (_, body) = parse_ckka(
+        "[補集合] [hsjoihs]\nTAI kauk2 TY. XI kauk2 XU.\nZO tam2 ZU XI. LE gua2 TE ZE ap1.\nTAU dau2 ZAI un1. CIA kaun1 XAU ZAI et2.\n[hsjoihs] zau io hop1 om2.\nta xot1.\n",
+    )
+    .unwrap();
+    assert_eq!(
+        serialize_body_in_notation3(&body).as_deref(),
+        Ok("\"taiPty\",\n\"xiPxu\",\n\"zoMzuxi\",\n\"leAteze4\",\n\"tauTzai5\",\n\"ciaCxauzai1=taxt=K\"\n")
+    );
+    let (_, body) =
+        parse_ckka("XI片XU無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 MAI兵MY無撃裁 或兵XU").unwrap();
+    assert_eq!(
+        serialize_body_in_notation3(&body).as_deref(),
+        Ok("\"xiPxu\",\n\"xaiPxy\",\n\"xuPxy\",\n\"maiPmy\",\n\"RPxu\"\n")
+    );
+
+    /* the moves of sample1 read back from MRF as they are, including `LIA巫NIANAU無撃裁`, which casts no stick */
+    let moves = |body: &Body| -> Vec<body::movement::Move> {
+        body.elems()
+            .filter_map(|elem| match elem {
+                body::Elem::Move(m, _) => Some(m.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    let text = serialize_body_in_notation3(&body).unwrap();
+    assert!(text.contains("\"liaSnianau=taxt=FHT/K\""));
+    let (_, read) = parse_ckka(&text).unwrap();
+    assert_eq!(moves(&read), moves(&body));
+    assert_eq!(serialize_body_in_notation3(&read), Ok(text));
+}
+
+#[test]
+fn relative_moves_of_sample() {
+    use super::*;
+    use body::{Action, Elem};
+    use relative::{perspective_of, relative_moves, to_absolute_move};
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    let moves = relative_moves(&body).unwrap();
+    let move_count = body
+        .elems()
+        .filter(|elem| matches!(elem, Elem::Move(..)))
+        .count();
+    assert_eq!(moves.len(), move_count);
+
+    for pair in moves.windows(2) {
+        /* the turn passes with every move within a season */
+        let season_ended = body.0[pair[0].index..pair[1].index]
+            .iter()
+            .any(|(elem, _)| matches!(elem, Elem::TaXotTyMok(_, Action::TaXot(_))));
+        if !season_ended {
+            assert_ne!(pair[0].mover, pair[1].mover);
+        }
+    }
+    for m in &moves {
+        match &body.0[m.index].0 {
+            Elem::Move(original, _) => assert_eq!(
+                &to_absolute_move(&m.movement, perspective_of(m.mover)),
+                original
+            ),
+            e => panic!("unexpected element {:?}", e),
+        }
+    }
+}
+
+#[test]
+fn turns_of_samples() {
+    use super::*;
+    use body::Elem;
+    use cetkaik_core::Color;
+    use turn::turns;
+    let (header, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    let sample = turns(&header, &body).unwrap();
+    assert_eq!(sample.errors, vec![]);
+    for (elem, mover) in body.elems().zip(sample.movers.iter()) {
+        match elem {
+            Elem::Move(..) => assert!(mover.as_ref().unwrap().player_name.is_some()),
+            _ => assert_eq!(mover, &None),
+        }
+    }
+
+    /* the names come from `{黒名:...}` and `{赤名:...}` when the header names no players */
+    let (header, body) = parse_ckka(
+        "{黒名:kuai1} {赤名:iei2}\n{一位色:赤}\n\"meAxeze3\",\n\"mauAmaimy1\",\n\"zoMnine\",\n\"ziaKxiacau\"\n",
+    )
+    .unwrap();
+    let named: Vec<_> = turns(&header, &body)
+        .unwrap()
+        .movers
+        .into_iter()
+        .map(|mover| {
+            let mover = mover.unwrap();
+            (mover.player_name.unwrap(), mover.color.unwrap())
+        })
+        .collect();
+    assert_eq!(
+        named,
+        vec![
+            (String::from("iei2"), Color::Kok1),
+            (String::from("kuai1"), Color::Huok2),
+            (String::from("iei2"), Color::Kok1),
+            (String::from("kuai1"), Color::Huok2),
+        ]
+    );
+}
+
+#[test]
+fn serialize_round_trip() {
+    use super::*;
+    use serialize::{serialize_ckka, NumeralStyle};
+    for s in &[
+        include_str!("../../sample1.txt"),
+        r"{律:硬皇力}
+{:2018年4月8日 17:30頃}
+[補集合] [hsjoihs]
+TAI kauk2 TY. XI kauk2 XU.
+ZO tam2 ZU XI. LE gua2 TE ZE ap1.
+TAU dau2 ZAI mun1. CIA kaun1 XAU ZAI et2.
+",
+        r"#{a}b}# {::}
+#[x]]#-3 [y]43
+LAU弓LAILY無{定弓失敗} LE弓LILU#{定弓}}#
+NAI片LAILY ZO皇[或]NU PAU皇[或]CAIMAU KE皇LI[KE]KA 黒車CI
+",
+    ] {
+        let parsed = parse_ckka(s).unwrap();
+        for style in &[NumeralStyle::Arabic, NumeralStyle::Pekzep] {
+            let text = serialize_ckka(&parsed.0, &parsed.1, *style).unwrap();
+            assert_eq!(parse_ckka(&text), Ok(parsed.clone()), "{text}");
+        }
+    }
+}
+
+#[test]
+fn serialize_numeral_round_trip() {
+    use super::serialize::{serialize_numeral, NumeralStyle};
+    for n in (-20000..=20000).chain(vec![
+        99_9999,
+        100_0000,
+        1234_5678,
+        1_0000_0000,
+        1_0000_0001,
+        21_4748_3647,
+    ]) {
+        assert_eq!(
+            pekzep_numeral::str_to_num(&serialize_numeral(n, NumeralStyle::Pekzep)),
+            Some(n),
+            "{n}"
+        );
+    }
+}
+
+#[test]
+fn parse_errors() {
+    use super::*;
+    use big_s::S;
+    use error::{Expected, ParseError, Position};
+    assert_eq!(
+        parse_ckka("{律:硬皇力}\r\n[SY] [JV]\r\nXU兵XY無撃裁 LY弓ZY水三此無\r\n"),
+        Err(ParseError::BadWaterStick {
+            position: Position {
+                line: 3,
+                column: 15
+            },
+            fragment: S("水三此無")
+        })
+    );
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁\n  LY弓Z無撃裁 ZY弓LY無撃裁\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position { line: 3, column: 6 },
+            fragment: S("LY弓Z無撃裁"),
+            expected: Expected::Square
+        })
+    );
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁\n[SY]為(王)終季\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position { line: 4, column: 1 },
+            fragment: S("[SY]為(王)終季"),
+            expected: Expected::Literal("手")
+        })
+    );
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁\n[SY]為(獣)(同色)再行\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position { line: 3, column: 9 },
+            fragment: S("[SY]為(獣)(同色)再行"),
+            expected: Expected::Hand
+        })
+    );
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁\n[SY]為(王)(同色王)再行\n")
+            .unwrap_err()
+            .position(),
+        Position { line: 3, column: 9 }
+    );
+    assert_eq!(
+        parse_ckka("{律:硬皇力} {始時:2021-01-01T00:00:00+09:00\n[SY] [JV]\n"),
+        Err(ParseError::Header {
+            position: Position { line: 1, column: 9 },
+            expected: Expected::ClosingBracket('}')
+        })
+    );
+    assert_eq!(
+        parse_ckka("{律:硬皇力}\n[SY]\nXU兵XY無撃裁\n")
+            .unwrap_err()
+            .position()
+            .line,
+        3
+    );
+
+    /* an error is reported against the notation that reads furthest, rather than always against notation ⓪ */
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nTAI kauk2 TY. XI kauk2 QU.\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position {
+                line: 2,
+                column: 24
+            },
+            fragment: S("XI"),
+            expected: Expected::Square
+        })
+    );
+    assert_eq!(
+        parse_ckka("\"xiPxu\", \"xaiPxy\", \"xuPxq\""),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position {
+                line: 1,
+                column: 20
+            },
+            fragment: S("\"xuPxq\""),
+            expected: Expected::BodyElem
+        })
+    );
+}
+
+#[test]
+fn consistency() {
+    use super::*;
+    use consistency::{check_consistency, ConsistencyError, Violation};
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    assert_eq!(check_consistency(&body), vec![]);
+
+    let (_, body) = parse_ckka(
+        r"
+    [SY] [JV]
+    XU兵XY無撃裁 手兵 NO船ZO水五
+    LY弓ZY無撃裁 ZI兵ZI無撃裁
+    LE弓TIZE橋四 ZO皇[ZU]CI ZO皇XICE
+    ",
+    )
+    .unwrap();
+    assert_eq!(
+        check_consistency(&body),
+        vec![
+            ConsistencyError {
+                index: 2,
+                violation: Violation::WaterStickFromWater
+            },
+            ConsistencyError {
+                index: 2,
+                violation: Violation::VesselWithWaterStick
+            },
+            ConsistencyError {
+                index: 3,
+                violation: Violation::MissingWaterStick
+            },
+            ConsistencyError {
+                index: 4,
+                violation: Violation::DestinationIsSource
+            },
+            ConsistencyError {
+                index: 5,
+                violation: Violation::NotInLine
+            },
+            ConsistencyError {
+                index: 6,
+                violation: Violation::NotInLine
+            },
+        ]
+    );
+}
+
+#[test]
+fn replay_sample1() {
+    use super::*;
+    use body::{Action, Elem};
+    use replay::{replay, State};
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    let states = replay(&body).collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(states.len(), body.0.len());
+
+    let ta_xot = body
+        .elems()
+        .position(|e| matches!(e, Elem::TaXotTyMok(_, Action::TaXot(_))))
+        .unwrap();
+    let before = &states[ta_xot - 1];
+    assert_eq!(
+        before.field.a_side_hop1zuo1.len() + before.field.ia_side_hop1zuo1.len(),
+        5
+    );
+    assert_eq!(states[ta_xot], State::initial());
+}
+
+#[test]
+fn resolve_wildcards() {
+    use super::*;
+    use body::movement::Move;
+    use body::Elem;
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    let wildcarded = Body(
+        body.0
+            .iter()
+            .map(|(elem, punctuation)| {
+                let elem = match elem {
+                    Elem::Move(Move::NoStepAndNoStick { src, dest, .. }, comment) => Elem::Move(
+                        Move::NoStepAndNoStick {
+                            src: *src,
+                            prof: None,
+                            dest: *dest,
+                        },
+                        comment.clone(),
+                    ),
+                    Elem::Move(
+                        Move::StepAndBridgeStick {
+                            src,
+                            step,
+                            dest,
+                            bridge_stick_size,
+                            bridge_stick_successful,
+                            ..
+                        },
+                        comment,
+                    ) => Elem::Move(
+                        Move::StepAndBridgeStick {
+                            src: *src,
+                            prof: None,
+                            step: *step,
+                            dest: *dest,
+                            bridge_stick_size: *bridge_stick_size,
+                            bridge_stick_successful: *bridge_stick_successful,
+                        },
+                        comment.clone(),
+                    ),
+                    elem => elem.clone(),
+                };
+                (elem, *punctuation)
+            })
+            .collect(),
+    );
+    assert_ne!(wildcarded, body);
+    assert_eq!(replay::resolve(&wildcarded), Ok(body));
+}
+
+#[test]
+fn parachute_of_unknown_color() {
+    use super::*;
+    use body::movement::Move;
+    use cetkaik_core::absolute::{Column, Coord, Row, Side};
+    use cetkaik_core::{Color, Profession};
+    use replay::{ReplayErrorKind, State};
+
+    let (_, m) = body::notation1::parse("或車CU").unwrap();
+    let mut state = State::initial();
+    state.side_to_move = Some(Side::ASide);
+    state
+        .field
+        .insert_nontam_piece_into_hop1zuo1(Color::Kok1, Profession::Kaun1, Side::ASide);
+    assert_eq!(
+        state.resolve_move(&m),
+        Ok(Move::Parachute {
+            color: Some(Color::Kok1),
+            prof: Profession::Kaun1,
+            dest: Coord(Row::U, Column::C),
+        })
+    );
+    assert_eq!(
+        state.apply_move(&m).unwrap().side_to_move,
+        Some(Side::IASide)
+    );
+
+    state
+        .field
+        .insert_nontam_piece_into_hop1zuo1(Color::Huok2, Profession::Kaun1, Side::ASide);
+    assert_eq!(
+        state.apply_move(&m),
+        Err(ReplayErrorKind::AmbiguousColor(Profession::Kaun1))
+    );
+
+    state.side_to_move = Some(Side::IASide);
+    assert_eq!(
+        state.apply_move(&m),
+        Err(ReplayErrorKind::NotInHand {
+            color: None,
+            prof: Profession::Kaun1
+        })
+    );
+}
+
+#[test]
+fn tam_step_of_unknown_stage() {
+    use super::*;
+    use body::movement::parse;
+    use cetkaik_core::absolute::{Column, Coord, Piece, Row, Side};
+    use cetkaik_core::{Color, Profession};
+    use replay::{ReplayErrorKind, State};
+
+    let mut state = State::initial();
+    state.field.board.insert(
+        Coord(Row::U, Column::Z),
+        Piece::NonTam2Piece {
+            color: Color::Huok2,
+            prof: Profession::Kauk2,
+            side: Side::ASide,
+        },
+    );
+    let (_, m) = parse("ZO皇ZUXI").unwrap();
+    assert_eq!(
+        state.resolve_move(&m),
+        Err(ReplayErrorKind::AmbiguousTamPath(Coord(Row::O, Column::Z)))
+    );
+
+    /* ZU is next to ZO, but XAU is too far from it */
+    assert_eq!(
+        state.resolve_move(&parse("ZO皇ZUXAU").unwrap().1),
+        Err(ReplayErrorKind::NoTamPath(Coord(Row::O, Column::Z)))
+    );
+
+    /* ZY is too far from ZU to step during the latter stage, and with TO and XO filled the former stage can only go back to ZO */
+    for c in &[Coord(Row::O, Column::T), Coord(Row::O, Column::X)] {
+        state.field.board.insert(
+            *c,
+            Piece::NonTam2Piece {
+                color: Color::Kok1,
+                prof: Profession::Kauk2,
+                side: Side::IASide,
+            },
+        );
+    }
+    assert_eq!(
+        state.resolve_move(&parse("ZO皇ZUZY").unwrap().1),
+        Ok(parse("ZO皇ZU[ZO]ZY").unwrap().1)
+    );
+}
+
+#[test]
+fn declarations() {
+    use super::*;
+    use body::hand::Hand;
+    use body::{Action, Elem};
+    use declaration::{check_declarations, DeclarationError, Issue};
+    let sample = include_str!("../../sample1.txt");
+    let check = |s: &str| check_declarations(&parse_ckka(s).unwrap().1).unwrap();
+    let (_, body) = parse_ckka(sample).unwrap();
+    let position = |action: Action| {
+        body.elems()
+            .position(|e| matches!(e, Elem::TaXotTyMok(_, a) if *a == action))
+            .unwrap()
+    };
+    let ty_mok = position(Action::TyMok);
+    let ta_xot = position(Action::TaXot(Some(20)));
+
+    assert_eq!(
+        check(&sample.replace("[SY]為(同色獣)(王)終季", "[SY]為(獣)(王)終季")),
+        vec![DeclarationError {
+            index: ta_xot,
+            issue: Issue::NotMade(Hand::Beasts)
+        }]
+    );
+    /* left undeclared, 同色獣 is given up, and cannot be declared at the end */
+    assert_eq!(
+        check(&sample.replace("[SY]為(同色獣)再行", "")),
+        vec![DeclarationError {
+            index: ta_xot - 1,
+            issue: Issue::GivenUp(Hand::SameColorBeasts)
+        }]
+    );
+    assert_eq!(
+        check(&sample.replace("[SY]為(同色獣)(王)終季", "[SY]為(同色獣)終季")),
+        vec![DeclarationError {
+            index: ta_xot,
+            issue: Issue::NotDeclared(Hand::King)
+        }]
+    );
+    assert_eq!(
+        check(&sample.replace(
+            "[SY]為(同色獣)再行",
+            "[SY]為(同色獣)再行 [SY]為(同色獣)再行"
+        )),
+        vec![DeclarationError {
+            index: ty_mok + 1,
+            issue: Issue::NoNewHand
+        }]
+    );
+
+    /* in example 4, 獣 is made by MU gua2 MY MIA but given up, and only 王 is declared */
+    let examples = readme_examples();
+    let (_, body) = parse_ckka(examples[3]).unwrap();
+    assert_eq!(check_declarations(&body), Ok(vec![]));
+
+    /* examples 5 and 6 declare hands that their pieces do not make under the standard rules */
+    let (_, body) = parse_ckka(examples[4]).unwrap();
+    assert_eq!(
+        check_declarations(&body),
+        Ok(vec![
+            DeclarationError {
+                index: 39,
+                issue: Issue::NotMade(Hand::Marching)
+            },
+            DeclarationError {
+                index: 39,
+                issue: Issue::NoNewHand
+            }
+        ])
+    );
+    let (_, body) = parse_ckka(examples[5]).unwrap();
+    assert_eq!(
+        check_declarations(&body),
+        Ok(vec![
+            DeclarationError {
+                index: 14,
+                issue: Issue::NoNewHand
+            },
+            DeclarationError {
+                index: 17,
+                issue: Issue::NotMade(Hand::WarGathering)
+            }
+        ])
+    );
+}
+
+#[test]
+fn hand_points_of_samples() {
+    use super::*;
+    use body::hand::Hand;
+    use body::{Action, Elem};
+    use std::collections::HashSet;
+    /* [SY] makes 同色獣 and 王, 七 and 三, and ends the season after one 再行 for 二十 */
+    for sample in &[
+        include_str!("../../sample1.txt"),
+        include_str!("../../sample2.txt"),
+    ] {
+        let (_, body) = parse_ckka(sample).unwrap();
+        let (hand_creation, points) = body
+            .elems()
+            .find_map(|elem| match elem {
+                Elem::TaXotTyMok(hand_creation, Action::TaXot(Some(points))) => {
+                    Some((hand_creation, *points))
+                }
+                _ => None,
+            })
+            .unwrap();
+        let hands = hand_creation.hands.clone().unwrap();
+        assert_eq!(
+            hands,
+            vec![Hand::SameColorBeasts, Hand::King]
+                .into_iter()
+                .collect::<HashSet<_>>()
+        );
+        assert_eq!(
+            hands.iter().map(|hand| hand.points()).sum::<i64>() * 2,
+            points
+        );
+    }
+}
+
+#[test]
+fn score_ledger() {
+    use super::*;
+    use big_s::S;
+    use error::{Expected, ParseError, Position};
+    use score::{ledger, ScoreError, ScoreIssue, Standing};
+    use serialize::{serialize_ckka, NumeralStyle};
+    let s = "[SY] [JV]
+XU兵XY無撃裁
+[SY]為(王)終季 手五
+春終
+[JV]十五 [SY] 二十五
+XU兵XY無撃裁
+[JV]為(獣)終季 手五
+[SY]二十 [JV]10
+";
+    let (header, body) = parse_ckka(s).unwrap();
+    let ledger = ledger(&header, &body).unwrap();
+    assert_eq!(
+        ledger.standings,
+        vec![
+            Standing {
+                index: 1,
+                points: [25, 15]
+            },
+            Standing {
+                index: 5,
+                points: [20, 20]
+            },
+        ]
+    );
+    assert_eq!(ledger.end, None);
+    assert_eq!(
+        ledger.errors,
+        vec![ScoreError {
+            index: 6,
+            issue: ScoreIssue::Mismatch {
+                written: [20, 10],
+                computed: [20, 20]
+            }
+        }]
+    );
+
+    let text = serialize_ckka(&header, &body, NumeralStyle::Arabic).unwrap();
+    assert_eq!(parse_ckka(&text), Ok((header, body)));
+
+    /* example 5 of the README writes no points after 終季, so the payout is worked out from the hands */
+    let (header, body) = parse_ckka(readme_examples()[4]).unwrap();
+    let ledger = score::ledger(&header, &body).unwrap();
+    assert_eq!(ledger.errors, vec![]);
+    assert_eq!(ledger.current(), [12, 28]);
+
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁\n[JV]十五 [SY]\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position {
+                line: 3,
+                column: 12
+            },
+            fragment: S("[JV]十五"),
+            expected: Expected::Numeral
+        })
+    );
+}
+
+#[test]
+fn seasons() {
+    use super::*;
+    use body::Season;
+    use season::{track_seasons, SeasonError, SeasonWarning};
+    let sample = include_str!("../../sample1.txt");
+    let ta_xot = "[SY]為(同色獣)(王)終季 手二十";
+    let warnings = |s: &str, first| {
+        track_seasons(&parse_ckka(s).unwrap().1, first)
+            .unwrap()
+            .warnings
+    };
+    let index = parse_ckka(sample).unwrap().1 .0.len() - 2;
+
+    assert_eq!(
+        warnings(&sample.replace("星一周", "上季終"), Season::Spring),
+        vec![]
+    );
+    assert_eq!(
+        warnings(&sample.replace("星一周", "夏終"), Season::Spring),
+        vec![SeasonError {
+            index: index + 1,
+            warning: SeasonWarning::WrongSeasonEnd {
+                written: Season::Summer,
+                actual: Season::Spring
+            }
+        }]
+    );
+    assert_eq!(
+        warnings(&sample.replace("星一周", "冬終"), Season::Winter),
+        vec![]
+    );
+    assert_eq!(
+        warnings(&sample.replace("[SY]為(同色獣)再行", ""), Season::Spring),
+        vec![SeasonError {
+            index: index - 1,
+            warning: SeasonWarning::PayoutMismatch {
+                written: 20,
+                computed: 3
+            }
+        }]
+    );
+    assert_eq!(
+        warnings(
+            &sample.replace(ta_xot, &format!("{ta_xot}\n{ta_xot}")),
+            Season::Winter
+        ),
+        vec![SeasonError {
+            index: index + 1,
+            warning: SeasonWarning::AfterLastSeason
+        }]
+    );
+    assert_eq!(
+        warnings(
+            &sample.replace("CE巫CI無撃裁", "CE巫CI無撃裁 春終"),
+            Season::Spring
+        ),
+        vec![SeasonError {
+            index: 7,
+            warning: SeasonWarning::NoSeasonToEnd
+        }]
+    );
+
+    /* 王 alone in example 4, and 獣 and 行行 across a 再行 in example 5, as the points of the hands are written */
+    let examples = readme_examples();
+    for (example, payout) in &[(examples[3], 3), (examples[4], 8)] {
+        let seasons = track_seasons(&parse_ckka(example).unwrap().1, Season::Spring).unwrap();
+        assert_eq!(seasons.records[0].payout, *payout);
+        assert_eq!(seasons.warnings, vec![]);
+    }
+}
+
+#[test]
+fn samples_check_clean() {
+    use super::*;
+    use body::Season;
+    /* the checks of `ckka check` */
+    for sample in &[
+        include_str!("../../sample1.txt"),
+        include_str!("../../sample2.txt"),
+    ] {
+        let (header, body) = parse_ckka(sample).unwrap();
+        assert!(replay::replay(&body).all(|state| state.is_ok()));
+        assert_eq!(consistency::check_consistency(&body), vec![]);
+        assert_eq!(declaration::check_declarations(&body), Ok(vec![]));
+        assert_eq!(capture::check_capture_comments(&body), Ok(vec![]));
+        let first = header.season().unwrap().unwrap_or(Season::Spring);
+        assert_eq!(
+            season::track_seasons(&body, first).unwrap().warnings,
+            vec![]
+        );
+        assert_eq!(turn::turns(&header, &body).unwrap().errors, vec![]);
+        assert_eq!(score::ledger(&header, &body).unwrap().errors, vec![]);
+    }
+}
+
+#[test]
+fn rule_variants() {
+    use super::*;
+    use replay::{replay_with, ReplayErrorKind};
+    use rule::Config;
+    let record = "{律:硬皇力}\n[SY] [JV]\nZO皇[ZU]ZY XAI兵XY無撃裁\nZY皇[ZU]ZO ZO皇[ZU]ZY\n";
+    let (header, body) = parse_ckka(record).unwrap();
+    assert!(replay::replay(&body).all(|state| state.is_ok()));
+
+    let config = Config::from_header(&header).unwrap();
+    let states: Vec<_> = replay_with(&body, config).collect();
+    assert_eq!(states.len(), 4);
+    let error = states[3].as_ref().unwrap_err();
+    assert_eq!(error.index, 3);
+    assert_eq!(
+        error.kind,
+        ReplayErrorKind::TamMovedTwiceInARow(cetkaik_core::absolute::Coord(
+            cetkaik_core::absolute::Row::O,
+            cetkaik_core::absolute::Column::Z
+        ))
+    );
+    assert_eq!(consistency::check_consistency(&body), vec![]);
+}
+
+#[test]
+fn punctuation_separators() {
+    use super::*;
+    use body::Punctuation;
+    use error::{Expected, ParseError, Position};
+    use serialize::{serialize_ckka, NumeralStyle};
+    let (header, body) =
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁、XAI兵XY無撃裁，XU兵XY無撃裁 。手兵\n").unwrap();
+    let (_, spaced) =
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁 XAI兵XY無撃裁 XU兵XY無撃裁 手兵\n").unwrap();
+    assert!(body.elems().eq(spaced.elems()));
+    let punctuation = |body: &Body| {
+        (0..body.0.len())
+            .map(|index| body.punctuation_after(index))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        punctuation(&body),
+        vec![
+            Some(Punctuation::IdeographicComma),
+            Some(Punctuation::FullwidthComma),
+            Some(Punctuation::IdeographicFullStop),
+            None
+        ]
+    );
+    assert_eq!(punctuation(&spaced), vec![None; 4]);
+
+    let text = serialize_ckka(&header, &body, NumeralStyle::Pekzep).unwrap();
+    assert_eq!(
+        text,
+        "[SY]二十 [JV]二十\nXU兵XY無撃裁、\nXAI兵XY無撃裁，\nXU兵XY無撃裁。 手兵\n"
+    );
+    assert_eq!(parse_ckka(&text).unwrap(), (header, body));
+
+    for c in ".,、。，．｡､".chars() {
+        let (_, body) = parse_ckka(&format!("XU兵XY無撃裁{c}XAI兵XY無撃裁")).unwrap();
+        assert_eq!(body.punctuation_after(0), Punctuation::from_char(c));
+    }
+
+    assert_eq!(
+        parse_ckka("[SY] [JV]\nXU兵XY無撃裁、XAI兵XY無撃裁、XU兵XY無撃\n"),
+        Err(ParseError::UnparsedBodyFragment {
+            position: Position {
+                line: 2,
+                column: 25
+            },
+            fragment: big_s::S("XU兵XY無撃"),
+            expected: Expected::Stick
+        })
+    );
+}
+
+#[test]
+fn capture_comments() {
+    use super::*;
+    use body::Elem;
+    use capture::{check_capture_comments, CaptureError, CaptureIssue};
+    use cetkaik_core::{Color, Profession};
+    let (_, body) = parse_ckka(include_str!("../../sample1.txt")).unwrap();
+    assert_eq!(check_capture_comments(&body), Ok(vec![]));
+
+    let record = "[SY] [JV]\nXI兵XU無撃裁 手兵 XAI兵XY無撃裁\nXU兵XY無撃裁 手赤兵 手兵\n";
+    let (header, body) = parse_ckka(record).unwrap();
+    assert_eq!(
+        body.0[4].0,
+        Elem::CaptureComment(Some(Color::Kok1), Profession::Kauk2)
+    );
+    assert_eq!(
+        check_capture_comments(&body),
+        Ok(vec![
+            CaptureError {
+                index: 1,
+                issue: CaptureIssue::NothingCaptured
+            },
+            CaptureError {
+                index: 5,
+                issue: CaptureIssue::NoPrecedingMove
+            }
+        ])
+    );
+    let text = serialize::serialize_ckka(&header, &body, serialize::NumeralStyle::Pekzep).unwrap();
+    assert!(text.contains("XU兵XY無撃裁 手赤兵 手兵\n"));
+    assert_eq!(parse_ckka(&text).unwrap(), (header, body));
+}
+
+#[test]
+fn bytes_and_line_endings() {
+    use super::*;
+    use error::{ParseError, Position};
+    let lf = include_str!("../../sample1.txt");
+    let crlf = lf.replace('\n', "\r\n");
+    assert_eq!(parse_ckka_bytes(crlf.as_bytes()), parse_ckka(lf));
+
+    /* an error is reported at the same place whichever line ending is used */
+    let broken = lf.replacen("手虎", "手象", 1);
+    let error = parse_ckka(&broken).unwrap_err();
+    assert_eq!(
+        parse_ckka_bytes(broken.replace('\n', "\r\n").as_bytes()),
+        Err(error)
+    );
+
+    assert_eq!(
+        parse_ckka(&format!("\u{feff}{lf}")),
+        Err(ParseError::ByteOrderMark)
+    );
+    assert_eq!(
+        parse_ckka_bytes(b"\xFF\xFE[\x00"),
+        Err(ParseError::ByteOrderMark)
+    );
+    let mut bytes = crlf.into_bytes();
+    bytes.push(0xC0);
+    assert_eq!(
+        parse_ckka_bytes(&bytes),
+        Err(ParseError::InvalidUtf8 {
+            position: Position {
+                line: lf.lines().count() + 1,
+                column: 1
+            }
+        })
+    );
+}
+
+#[test]
+fn incremental_parser() {
+    use super::*;
+    use body::Notation;
+    use error::{Expected, ParseError, Position};
+    use incremental::IncrementalParser;
+    let sample = include_str!("../../sample1.txt").replace('\n', "\r\n");
+    let expected = parse_ckka(&sample).unwrap();
+    let chars: Vec<char> = sample.chars().collect();
+    for chunk_size in 1..=7 {
+        let mut parser = IncrementalParser::new();
+        let mut elems = vec![];
+        for chunk in chars.chunks(chunk_size) {
+            elems.extend(parser.feed(&chunk.iter().collect::<String>()).unwrap());
+        }
+        assert_eq!(elems, expected.1.elems().cloned().collect::<Vec<_>>());
+        assert_eq!(parser.finish().unwrap(), expected);
+    }
+
+    /* a punctuation mark may come after a newline */
+    let mut parser = IncrementalParser::new();
+    parser.feed("XU兵XY無撃裁\n").unwrap();
+    parser.feed("、XAI兵XY無撃裁").unwrap();
+    assert_eq!(parser.finish(), parse_ckka("XU兵XY無撃裁\n、XAI兵XY無撃裁"));
+
+    let error = ParseError::UnparsedBodyFragment {
+        position: Position { line: 2, column: 6 },
+        fragment: big_s::S("XU兵XY無撃"),
+        expected: Expected::Stick,
+    };
+    let mut parser = IncrementalParser::new();
+    assert_eq!(parser.feed("[SY] [JV]\nXU兵XY無撃"), Ok(vec![]));
+    assert_eq!(parser.pending(), "XU兵XY無撃");
+    assert_eq!(parser.clone().finish(), Err(error.clone()));
+    assert_eq!(parser.feed("\n"), Err(error.clone()));
+    assert_eq!(parser.feed("裁\n"), Err(error));
+
+    /* the notation is detected from the first complete element */
+    let pekzep = "{律:硬皇力}\n[補集合] [hsjoihs]\nTAI kauk2 TY. XI kauk2 XU.\nZO tam2 ZU XI. LE gua2 TE ZE ap1.\nTAU dau2 ZAI mun1. CIA kaun1 XAU ZAI et2.\n[hsjoihs] zau io hop1 om2.\nta xot1.\n";
+    let mrf = "[SY] [JV]\n\"xiPxu\", \"xaiPxy\", \"mauAmaimy1\"\n";
+    let mixed = "XU兵XY無撃裁 XAI兵XY LE弓ZE\n";
+    for (record, notation) in &[
+        (pekzep, Notation::Two),
+        (mrf, Notation::Three),
+        (mixed, Notation::One),
+    ] {
+        let expected = parse_ckka(record).unwrap();
+        let chars: Vec<char> = record.chars().collect();
+        for chunk_size in 1..=5 {
+            let mut parser = IncrementalParser::new();
+            for chunk in chars.chunks(chunk_size) {
+                parser.feed(&chunk.iter().collect::<String>()).unwrap();
+            }
+            assert_eq!(parser.notation(), Some(*notation));
+            assert_eq!(parser.finish().unwrap(), expected);
+        }
+    }
+    let mut parser = IncrementalParser::new();
+    parser.feed("XU兵XY無撃裁 ").unwrap();
+    assert_eq!(parser.notation(), Some(Notation::Zero));
+    parser.feed("XAI兵XY ").unwrap();
+    assert_eq!(parser.notation(), Some(Notation::One));
+
+    /* a notation that is given is not detected */
+    let mut parser = IncrementalParser::with_notation(Notation::Zero);
+    parser.feed("[SY] [JV]\n").unwrap();
+    assert!(parser.feed("TAI kauk2 TY. ").is_err());
+}
+
+#[test]
+fn columns_and_other_notations() {
+    use super::*;
+    use body::{Action, Elem};
+    use serialize::*;
+    let sample = include_str!("../../sample1.txt");
+    let pekzep = "{律:硬皇力}\n[補集合] [hsjoihs]\nTAI kauk2 TY. XI kauk2 XU.\nZO tam2 ZU XI. LE gua2 TE ZE ap1.\nTAU dau2 ZAI mun1. CIA kaun1 XAU ZAI et2.\n[hsjoihs] zau io hop1 om2.\nta xot1.\n";
+
+    /* the layout of the sample is reproduced, except that the hands are sorted */
+    let (_, body) = parse_ckka(sample).unwrap();
+    let expected: String = sample
+        .lines()
+        .skip(3)
+        .map(|line| line.trim_end().to_owned() + "\n")
+        .collect::<String>()
+        .replace("(同色獣)(王)", "(王)(同色獣)");
+    assert_eq!(
+        serialize_body_in_columns(&body, NumeralStyle::Pekzep).unwrap(),
+        expected
+    );
+
+    /* notation ⓪ has no way to leave out what the taxot of the record in notation ② pays out */
+    for s in &[sample, pekzep] {
+        let (header, body) = parse_ckka(s).unwrap();
+        let mut texts = vec![serialize_body_in_notation1(&body, NumeralStyle::Pekzep).unwrap()];
+        texts.extend(serialize_body_in_columns(&body, NumeralStyle::Pekzep));
+        for text in &texts {
+            let text = format!(
+                "{}{}",
+                serialize_header(&header, NumeralStyle::Arabic),
+                text
+            );
+            assert_eq!(
+                parse_ckka(&text),
+                Ok((header.clone(), body.clone())),
+                "{text}"
+            );
+            assert_eq!(
+                body_elem_positions(&text).map(|p| p.len()),
+                Some(body.0.len())
+            );
+        }
+    }
+
+    /* `LIA巫NIANAU無撃裁` casts no bridge stick, which notation ② says by writing no sticks */
+    let text = serialize_body_in_notation2(&body).unwrap();
+    assert!(text.contains("LIA tuk2 NIA NAU.\n"));
+    let written: Vec<Elem> = body
+        .0
+        .into_iter()
+        .filter_map(|(elem, _)| match elem {
+            Elem::TaXotTyMok(hand_creation, Action::TaXot(_)) => {
+                Some(Elem::TaXotTyMok(hand_creation, Action::TaXot(None)))
+            }
+            Elem::Move(..) | Elem::TaXotTyMok(..) => Some(elem),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(parse_body(&text).unwrap().1, Body::from(written));
+
+    let (header, body) = parse_ckka(pekzep).unwrap();
+    let text = format!("{}{}", header, serialize_body_in_notation2(&body).unwrap());
+    assert_eq!(parse_ckka(&text), Ok((header, body)));
+}
+
+#[test]
+fn issue_messages() {
+    use super::*;
+    use cetkaik_core::absolute::{Column, Coord, NonTam2Piece, Row};
+    use cetkaik_core::{Color, Profession};
+    assert_eq!(
+        replay::ReplayErrorKind::NoPieceAt(Coord(Row::U, Column::X)).to_string(),
+        "there is no piece on XU"
+    );
+    assert_eq!(
+        consistency::Violation::VesselWithWaterStick.to_string(),
+        "a vessel casts a water stick"
+    );
+    assert_eq!(
+        declaration::Issue::NotMade(body::hand::Hand::Beasts).to_string(),
+        "`獣` is declared, but the pieces in hand do not make it"
+    );
+    assert_eq!(
+        capture::CaptureIssue::Mismatch {
+            written_color: None,
+            written_prof: Profession::Kauk2,
+            actual: NonTam2Piece {
+                color: Color::Kok1,
+                prof: Profession::Gua2
+            }
+        }
+        .to_string(),
+        "`手兵` is written, but the move before captures 赤弓"
+    );
+    assert_eq!(
+        season::SeasonWarning::WrongSeasonEnd {
+            written: body::Season::Summer,
+            actual: body::Season::Spring
+        }
+        .to_string(),
+        "`夏終` is written, but the season that has just ended is 春"
+    );
+    assert_eq!(
+        score::ScoreIssue::Mismatch {
+            written: [25, 15],
+            computed: [30, 10]
+        }
+        .to_string(),
+        "the points are written as 25 and 15, but come to 30 and 10"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_round_trip() {
+    use super::*;
+    use body::movement::Move;
+    use body::{Action, Body};
+    use cetkaik_core::absolute::{Column, Coord, Row};
+    use cetkaik_core::Profession;
+    for s in &[
+        include_str!("../../sample1.txt"),
+        "{律:硬皇力}\n[補集合] [hsjoihs]\nTAI kauk2 TY. ZO tam2 ZU XI. LE gua2 TE ZE ap1.\n[hsjoihs] zau io hop1 om2.\nta xot1.\n",
+        "#{a}b}# {::}\n#[x]]#-3 [y]43\nLAU弓LAILY無{定弓失敗}、LE弓LILU#{定弓}}#\nNAI片LAILY ZO皇[或]NU PAU皇[或]CAIMAU KE皇LI[KE]KA 或車CI 手馬\n[x]為(獣)終季 手五 春終 [x]-3 [y]43 [y]為(王)再行 星一周\n",
+    ] {
+        let (header, body) = parse_ckka(s).unwrap();
+        let json = serde_json::to_string(&(&header, &body)).unwrap();
+        assert_eq!(serde_json::from_str(&json).ok(), Some((header, body)), "{json}");
+    }
+
+    /* the hands are sorted, and a profession may also be given by its romanization */
+    let (_, body) = parse_ckka("XU兵XY無撃裁 [SY]為(同色獣)(王)(獣)終季 手五").unwrap();
+    let json = serde_json::to_string(&body.0[1].0).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"ta_xot_ty_mok","hand_creation":{"player_name":"SY","hands":["王","獣","同色獣"],"points":null},"action":{"type":"ta_xot","points":5}}"#
+    );
+    let m: Move = serde_json::from_str(
+        r#"{"type":"step_and_bridge_stick","src":"LE","prof":"gua2","step":"TE","dest":"ZE","bridge_stick_size":4,"bridge_stick_successful":true}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        m,
+        Move::StepAndBridgeStick {
+            src: Coord(Row::E, Column::L),
+            prof: Some(Profession::Gua2),
+            step: Coord(Row::E, Column::T),
+            dest: Coord(Row::E, Column::Z),
+            bridge_stick_size: Some(4),
+            bridge_stick_successful: true,
+        }
+    );
+    assert_eq!(
+        serde_json::to_string(&Action::TyMok).unwrap(),
+        r#"{"type":"ty_mok"}"#
+    );
+
+    /* `punctuation` may be left out */
+    assert_eq!(
+        serde_json::from_str::<Body>(r#"{"elems":[{"type":"game_end"}]}"#).ok(),
+        Some(Body::from(vec![body::Elem::GameEnd]))
+    );
+    assert!(serde_json::from_str::<Move>(
+        r#"{"type":"no_step_and_no_stick","src":"XU","prof":"象","dest":"XY"}"#
+    )
+    .is_err());
+
+    /* a stick shows 0 to 5 */
+    for size in &["12", "-1"] {
+        assert!(serde_json::from_str::<Move>(&format!(
+            r#"{{"type":"no_step_and_water_stick","src":"LY","prof":"弓","dest":"ZY","water_stick_size":{size},"water_stick_successful":true}}"#
+        ))
+        .is_err());
+    }
+    assert!(serde_json::from_str::<Move>(
+        r#"{"type":"step_and_bridge_stick","src":"ME","prof":"弓","step":"MI","dest":"MU","bridge_stick_size":6,"bridge_stick_successful":true}"#
+    )
+    .is_err());
+    assert!(serde_json::from_str::<Move>(
+        r#"{"type":"step_and_bridge_stick","src":"ME","prof":"弓","step":"MI","dest":"MU","bridge_stick_size":null,"bridge_stick_successful":true}"#
+    )
+    .is_ok());
+}
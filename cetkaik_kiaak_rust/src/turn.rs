@@ -0,0 +1,136 @@
+use super::body::{Action, Body, Elem};
+use super::header::Header;
+use super::relative::movers;
+use super::replay::ReplayError;
+use cetkaik_core::absolute::Side;
+use cetkaik_core::Color;
+use std::fmt;
+
+/// The player who makes a move.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Mover {
+    pub side: Side,
+
+    /// The name from `Header.players`, or from `{黒名:...}` and `{赤名:...}` if the header names no players
+    pub player_name: Option<String>,
+
+    /// The colour given to the player, from `{一位色:...}` and the order of the players
+    pub color: Option<Color>,
+}
+
+/// A disagreement between `[player]為(...)` and the player who made the move before it.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum TurnIssue {
+    /// The declaration names a player other than the one who made the last move
+    NotTheMover { written: String, mover: String },
+}
+
+/// An issue found in a body, together with the index of the declaration in `Body.0`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct TurnError {
+    pub index: usize,
+    pub issue: TurnIssue,
+}
+
+impl fmt::Display for TurnIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnIssue::NotTheMover { written, mover } => write!(
+                f,
+                "the declaration names {written}, but {mover} made the last move"
+            ),
+        }
+    }
+}
+
+/// The player of each move of a body. See [`turns`].
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Turns {
+    /// The player of each element, indexed in the same way as `Body.0`.
+    /// Elements other than moves, and moves whose side cannot be found out, are `None`.
+    pub movers: Vec<Option<Mover>>,
+
+    pub errors: Vec<TurnError>,
+}
+
+const fn other_color(color: Color) -> Color {
+    match color {
+        Color::Kok1 => Color::Huok2,
+        Color::Huok2 => Color::Kok1,
+    }
+}
+
+/// Replays the body and tags each move with the player who makes it.
+///
+/// The player who makes the first move of the body is the first of `Header.players`, and has the colour given by `{一位色:...}`;
+/// each player keeps their side of the board throughout the game.
+/// A value of `{一位色:...}` that cannot be read is treated as if it were not given.
+/// Every `[player]為(...)` is checked against the player who made the move before it.
+///
+/// Examples:
+/// ```
+/// use cetkaik_kiaak::parse_ckka;
+/// use cetkaik_kiaak::turn::{turns, TurnError, TurnIssue};
+/// use cetkaik_core::Color;
+/// let (header, body) = parse_ckka("{一位色:黒}\n[SY] [JV]\nXI兵XU無撃裁 XAI兵XY無撃裁\nXU兵XY無撃裁 手赤兵 [JV]為(王)終季 手十\n").unwrap();
+/// let turns = turns(&header, &body).unwrap();
+/// let mover = turns.movers[1].as_ref().unwrap();
+/// assert_eq!(mover.player_name.as_deref(), Some("JV"));
+/// assert_eq!(mover.color, Some(Color::Kok1));
+/// assert_eq!(turns.movers[2].as_ref().unwrap().player_name.as_deref(), Some("SY"));
+/// assert_eq!(turns.movers[3], None);
+/// assert_eq!(
+///     turns.errors,
+///     vec![TurnError {
+///         index: 4,
+///         issue: TurnIssue::NotTheMover { written: String::from("JV"), mover: String::from("SY") }
+///     }]
+/// );
+/// ```
+pub fn turns(header: &Header, body: &Body) -> Result<Turns, ReplayError> {
+    let sides = movers(body)?;
+    let first_side = sides.iter().find_map(|side| *side);
+    let first_color = header.first_color().ok().flatten();
+
+    let mover_of = |side: Side| {
+        let first = Some(side) == first_side;
+        let color = first_color.map(|color| if first { color } else { other_color(color) });
+        let player_name = match &header.players {
+            Some((p1, p2)) => Some(if first { p1 } else { p2 }.player_name.clone()),
+            None => color.and_then(|color| header.player_name(color).map(str::to_owned)),
+        };
+        Mover {
+            side,
+            player_name,
+            color,
+        }
+    };
+    let movers: Vec<_> = sides.into_iter().map(|side| side.map(mover_of)).collect();
+
+    let mut errors = vec![];
+    let mut last_mover: Option<&Mover> = None;
+    for (index, (elem, mover)) in body.elems().zip(movers.iter()).enumerate() {
+        match elem {
+            Elem::Move(..) => last_mover = mover.as_ref(),
+            Elem::TaXotTyMok(hand_creation, action) => {
+                let mover_name = last_mover.and_then(|mover| mover.player_name.as_ref());
+                if let (Some(name), Some(written)) = (mover_name, &hand_creation.player_name) {
+                    if name != written {
+                        errors.push(TurnError {
+                            index,
+                            issue: TurnIssue::NotTheMover {
+                                written: written.clone(),
+                                mover: name.clone(),
+                            },
+                        });
+                    }
+                }
+                if let Action::TaXot(_) = action {
+                    last_mover = None;
+                }
+            }
+            Elem::CaptureComment(..) | Elem::SeasonEnd(_) | Elem::GameEnd | Elem::Points(..) => {}
+        }
+    }
+    Ok(Turns { movers, errors })
+}